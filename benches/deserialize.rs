@@ -0,0 +1,85 @@
+// Compares `from_value`, a `ValueDeserializer` and parsing from text on a small
+// message. Run with `cargo bench`.
+
+#![allow(non_local_definitions)]
+
+use miniserde::json::{self, Value};
+use miniserde::Deserialize;
+use miniserde_from_value::{from_value, ValueDeserializer};
+use std::hint::black_box;
+use std::time::Instant;
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Message {
+    id: u64,
+    kind: String,
+    tags: Vec<String>,
+    position: Position,
+    ratio: f64,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Position {
+    x: i32,
+    y: i32,
+}
+
+const INPUT: &str = r#"{
+    "id": 12345,
+    "kind": "update",
+    "tags": ["a", "b", "c"],
+    "position": {"x": -3, "y": 7},
+    "ratio": 0.25
+}"#;
+
+const ITERATIONS: u32 = 50_000;
+const ROUNDS: usize = 21;
+
+// Time each case over several interleaved rounds, so that one noisy round or
+// a drift in clock speed does not decide the comparison, and report the
+// median with the fastest and slowest rounds.
+fn bench(cases: &mut [(&str, &mut dyn FnMut())]) {
+    let mut samples = vec![Vec::with_capacity(ROUNDS); cases.len()];
+    for (_, f) in cases.iter_mut() {
+        for _ in 0..ITERATIONS {
+            f();
+        }
+    }
+    for _ in 0..ROUNDS {
+        for ((_, f), samples) in cases.iter_mut().zip(&mut samples) {
+            let start = Instant::now();
+            for _ in 0..ITERATIONS {
+                f();
+            }
+            samples.push(start.elapsed().as_nanos() as f64 / f64::from(ITERATIONS));
+        }
+    }
+    for ((name, _), samples) in cases.iter().zip(&mut samples) {
+        samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        println!(
+            "{:<24} {:>8.1} ns/iter (min {:.1}, max {:.1})",
+            name,
+            samples[ROUNDS / 2],
+            samples[0],
+            samples[ROUNDS - 1],
+        );
+    }
+}
+
+fn main() {
+    let value: Value = json::from_str(INPUT).unwrap();
+    let mut de = ValueDeserializer::new();
+    bench(&mut [
+        ("from_value", &mut || {
+            black_box(from_value::<Message>(black_box(&value)).unwrap());
+        }),
+        ("ValueDeserializer", &mut || {
+            black_box(de.deserialize::<Message>(black_box(&value)).unwrap());
+        }),
+        ("json::from_str", &mut || {
+            black_box(json::from_str::<Message>(black_box(INPUT)).unwrap());
+        }),
+    ]);
+}
@@ -0,0 +1,171 @@
+use miniserde::json::{Array, Number, Object, Value};
+
+static NULL: Value = Value::Null;
+
+/// Typed accessors for `Value`, for inspecting a document without matching
+/// on it.
+///
+/// Every accessor returns `None` when the Value has another type.
+pub trait ValueExt {
+    fn as_str(&self) -> Option<&str>;
+
+    /// Unsigned integers, and signed ones that are not negative.
+    fn as_u64(&self) -> Option<u64>;
+
+    /// Signed integers, and unsigned ones up to `i64::MAX`.
+    fn as_i64(&self) -> Option<i64>;
+
+    /// Any number, possibly losing precision for large integers.
+    fn as_f64(&self) -> Option<f64>;
+
+    fn as_bool(&self) -> Option<bool>;
+
+    fn as_array(&self) -> Option<&Array>;
+
+    fn as_object(&self) -> Option<&Object>;
+
+    fn is_null(&self) -> bool;
+
+    /// Member of an object.
+    fn get(&self, key: &str) -> Option<&Value>;
+
+    /// Element of an array.
+    fn get_index(&self, i: usize) -> Option<&Value>;
+
+    /// Member of an object or element of an array, or a shared `null` when
+    /// there is none, so lookups chain as in `v.at("items").at(0).at("id")`.
+    ///
+    /// This stands in for `v["items"][0]`: `Index` cannot be implemented for
+    /// `Value` outside of miniserde.
+    fn at<I: ValueIndex>(&self, index: I) -> &Value;
+}
+
+/// A key or array index accepted by `ValueExt::at`.
+pub trait ValueIndex {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value>;
+}
+
+impl ValueIndex for str {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        v.get(self)
+    }
+}
+
+impl ValueIndex for String {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        v.get(self)
+    }
+}
+
+impl ValueIndex for usize {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        v.get_index(*self)
+    }
+}
+
+impl<T: ?Sized + ValueIndex> ValueIndex for &T {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        (**self).index_into(v)
+    }
+}
+
+impl ValueExt for Value {
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Value::Number(Number::U64(n)) => Some(n),
+            Value::Number(Number::I64(n)) if n >= 0 => Some(n as u64),
+            _ => None,
+        }
+    }
+
+    fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Number(Number::I64(n)) => Some(n),
+            Value::Number(Number::U64(n)) if n <= i64::MAX as u64 => Some(n as i64),
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Number(Number::U64(n)) => Some(n as f64),
+            Value::Number(Number::I64(n)) => Some(n as f64),
+            Value::Number(Number::F64(n)) => Some(n),
+            _ => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    fn as_array(&self) -> Option<&Array> {
+        match self {
+            Value::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    fn as_object(&self) -> Option<&Object> {
+        match self {
+            Value::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+
+    fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    fn get(&self, key: &str) -> Option<&Value> {
+        self.as_object()?.get(key)
+    }
+
+    fn get_index(&self, i: usize) -> Option<&Value> {
+        self.as_array()?.get(i)
+    }
+
+    fn at<I: ValueIndex>(&self, index: I) -> &Value {
+        index.index_into(self).unwrap_or(&NULL)
+    }
+}
+
+#[test]
+fn accessors() {
+    use miniserde::json;
+
+    let v: Value = json::from_str(
+        r#"{"items": [{"id": 1, "name": "a"}, {"id": -2, "price": 2.5}], "ok": true, "n": null}"#,
+    )
+    .unwrap();
+    let items = v.at("items");
+    assert_eq!(items.as_array().map(|a| a.len()), Some(2));
+    assert_eq!(items.at(0).at("id").as_u64(), Some(1));
+    assert_eq!(items.at(0).at("id").as_i64(), Some(1));
+    assert_eq!(items.at(0).at("name").as_str(), Some("a"));
+    assert_eq!(items.at(1).at("id").as_u64(), None);
+    assert_eq!(items.at(1).at("id").as_i64(), Some(-2));
+    assert_eq!(items.at(1).at("id").as_f64(), Some(-2.0));
+    assert_eq!(items.at(1).at("price".to_owned()).as_f64(), Some(2.5));
+    assert_eq!(v.at("ok").as_bool(), Some(true));
+    assert!(v.at("n").is_null());
+    assert!(v.at("missing").at(3).at("x").is_null());
+    assert!(v.get("n").is_some());
+    assert!(v.get("missing").is_none());
+    assert!(items.get_index(1).is_some());
+    assert!(items.get_index(2).is_none());
+    assert!(items.get("id").is_none());
+    assert_eq!(v.as_object().map(|o| o.len()), Some(3));
+    assert!(v.as_str().is_none());
+    assert!(!v.is_null());
+}
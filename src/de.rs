@@ -0,0 +1,819 @@
+use crate::error::{Callback, DetailedError, ErrorKind, Limit, Unexpected};
+use crate::pointer::escape;
+use miniserde::{
+    de::{Map, Seq, Visitor},
+    json::{Number, Object, Value},
+    Deserialize, Error,
+};
+use std::collections::btree_map;
+use std::fmt::Write;
+use std::iter::Enumerate;
+use std::mem;
+use std::slice;
+
+/// Settings for deserializing a `Value` with detailed error reporting.
+///
+/// All limits are disabled by default. Set them before handing untrusted
+/// input to `from_value`, which then fails with `ErrorKind::LimitExceeded`
+/// instead of walking the whole document.
+#[derive(Clone, Debug, Default)]
+pub struct FromValueOptions {
+    max_depth: Option<usize>,
+    max_total_nodes: Option<usize>,
+    max_string_len: Option<usize>,
+    max_collection_len: Option<usize>,
+    deny_unknown_keys: bool,
+    coerce: bool,
+}
+
+impl FromValueOptions {
+    pub fn new() -> Self {
+        FromValueOptions::default()
+    }
+
+    /// Maximum number of nested arrays and objects. A scalar at the root has
+    /// depth 0.
+    pub fn max_depth(mut self, limit: usize) -> Self {
+        self.max_depth = Some(limit);
+        self
+    }
+
+    /// Maximum number of values visited, containers included.
+    pub fn max_total_nodes(mut self, limit: usize) -> Self {
+        self.max_total_nodes = Some(limit);
+        self
+    }
+
+    /// Maximum length in bytes of strings and object keys.
+    pub fn max_string_len(mut self, limit: usize) -> Self {
+        self.max_string_len = Some(limit);
+        self
+    }
+
+    /// Maximum number of elements in an array or entries in an object.
+    pub fn max_collection_len(mut self, limit: usize) -> Self {
+        self.max_collection_len = Some(limit);
+        self
+    }
+
+    /// Fail with `ErrorKind::UnknownKey` on object keys that the target type
+    /// does not use.
+    ///
+    /// A key counts as unknown when `Map::key` hands out miniserde's ignore
+    /// sink, which is what derived structs do for fields they do not declare.
+    /// The sink is recognized by being zero-sized, so a hand-written `Map`
+    /// whose key visitor is zero-sized has its keys rejected too. Miniserde
+    /// does not expose the declared field names, so no correction can be
+    /// suggested.
+    pub fn deny_unknown_keys(mut self, deny: bool) -> Self {
+        self.deny_unknown_keys = deny;
+        self
+    }
+
+    /// Retry scalars the target rejects in a converted form: numeric strings
+    /// as numbers, `"true"` and `"false"` as booleans, integral floats such as
+    /// `3.0` or `"1e3"` as integers and integers as floats.
+    pub fn coerce(mut self, coerce: bool) -> Self {
+        self.coerce = coerce;
+        self
+    }
+
+    /// Deserialize `v` according to these options.
+    pub fn from_value<T: Deserialize>(&self, v: &Value) -> Result<T, DetailedError> {
+        let mut out = None;
+        self.drive(v, T::begin(&mut out))?;
+        out.ok_or_else(|| root_error(v))
+    }
+
+    /// Deserialize `v` according to these options, also returning the JSON
+    /// Pointers of every object key the target type ignored.
+    ///
+    /// Ignored subtrees are not descended into, so only the outermost ignored
+    /// key of each is reported. As with `deny_unknown_keys`, any zero-sized
+    /// visitor returned by `Map::key` is taken for miniserde's ignore sink.
+    pub fn from_value_report<T: Deserialize>(
+        &self,
+        v: &Value,
+    ) -> Result<(T, Vec<String>), DetailedError> {
+        let mut out = None;
+        let mut ignored = Vec::new();
+        self.run(v, T::begin(&mut out), Some(&mut ignored), None)?;
+        match out {
+            Some(out) => Ok((out, ignored)),
+            None => Err(root_error(v)),
+        }
+    }
+
+    /// Deserialize `v` according to these options, carrying on after a
+    /// failure and returning every error encountered.
+    ///
+    /// The subtree that failed is skipped and deserialization continues with
+    /// its siblings. When a container's `finish` fails after an error inside
+    /// it, the failure is still reported, as a field may really be missing,
+    /// but marked with `DetailedError::follows_child_error`. Exceeding a
+    /// limit still stops deserialization immediately.
+    pub fn from_value_collect<T: Deserialize>(&self, v: &Value) -> Result<T, Vec<DetailedError>> {
+        let mut out = None;
+        let mut errors = Vec::new();
+        if let Err(err) = self.run(v, T::begin(&mut out), None, Some(&mut errors)) {
+            errors.push(err);
+        }
+        match out {
+            Some(out) if errors.is_empty() => Ok(out),
+            None if errors.is_empty() => Err(vec![root_error(v)]),
+            _ => Err(errors),
+        }
+    }
+
+    /// Feed `v` to `visitor` according to these options. This is the
+    /// detailed counterpart of `drive`.
+    pub fn drive(&self, v: &Value, visitor: &mut dyn Visitor) -> Result<(), DetailedError> {
+        self.run(v, visitor, None, None)
+    }
+
+    fn run(
+        &self,
+        v: &Value,
+        visitor: &mut dyn Visitor,
+        ignored: Option<&mut Vec<String>>,
+        errors: Option<&mut Vec<DetailedError>>,
+    ) -> Result<(), DetailedError> {
+        let mut de = Deserializer {
+            options: self,
+            path: Vec::new(),
+            stack: Vec::new(),
+            nodes: 0,
+            ignored,
+            errors,
+        };
+        de.push(None, v, visitor)?;
+        while let Some(event) = de.stack.pop() {
+            match event {
+                Event::Visitor(segment, v, visitor) => {
+                    de.path.extend(segment);
+                    if let Err(err) = de.visit(v, visitor) {
+                        de.fail(err)?;
+                    }
+                }
+                Event::Seq(depth, mut arr, mut seq) => {
+                    de.path.truncate(depth);
+                    match arr.next() {
+                        Some((i, v)) => {
+                            let segment = Segment::Index(i);
+                            let element = seq.element().map(|e| careful!(e as &mut dyn Visitor));
+                            de.stack.push(Event::Seq(depth, arr, seq));
+                            match element {
+                                Ok(element) => de.push(Some(segment), v, element)?,
+                                Err(_) => de.fail(de.error(
+                                    Some(segment),
+                                    Callback::Element,
+                                    ErrorKind::Rejected,
+                                ))?,
+                            }
+                        }
+                        None => {
+                            if seq.finish().is_err() {
+                                de.finish_failed(Callback::SeqFinish, ErrorKind::Rejected)?;
+                            }
+                        }
+                    }
+                }
+                Event::Map(depth, o, mut obj, mut map) => {
+                    de.path.truncate(depth);
+                    match obj.next() {
+                        Some((k, v)) => {
+                            let segment = Segment::Key(k);
+                            de.check_len(Some(segment), k, Callback::Key)?;
+                            let key = map.key(k).map(|key| careful!(key as &mut dyn Visitor));
+                            de.stack.push(Event::Map(depth, o, obj, map));
+                            match key {
+                                Ok(key) if self.deny_unknown_keys && is_ignore(key) => de.fail(
+                                    de.error(Some(segment), Callback::Key, ErrorKind::UnknownKey),
+                                )?,
+                                Ok(key) if de.ignored.is_some() && is_ignore(key) => {
+                                    if let Some(ignored) = &mut de.ignored {
+                                        ignored.push(pointer(&de.path, Some(segment)));
+                                    }
+                                }
+                                Ok(key) => de.push(Some(segment), v, key)?,
+                                Err(_) => de.fail(de.error(
+                                    Some(segment),
+                                    Callback::Key,
+                                    ErrorKind::Rejected,
+                                ))?,
+                            }
+                        }
+                        None => {
+                            if map.finish().is_err() {
+                                let keys = o.keys().cloned().collect();
+                                de.finish_failed(Callback::MapFinish, ErrorKind::Incomplete(keys))?;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Same as `from_value`, but on failure reports the JSON Pointer of the node
+/// being processed and the visitor callback that rejected it.
+pub fn from_value_detailed<T: Deserialize>(v: &Value) -> Result<T, DetailedError> {
+    FromValueOptions::new().from_value(v)
+}
+
+/// Same as `from_value_detailed`, but also returns the JSON Pointers of every
+/// object key the target type ignored, for example to warn about typos or
+/// deprecated settings.
+pub fn from_value_report<T: Deserialize>(v: &Value) -> Result<(T, Vec<String>), DetailedError> {
+    FromValueOptions::new().from_value_report(v)
+}
+
+/// Same as `from_value_detailed`, but keeps going after a failure and returns
+/// every error with its path, so that all mistakes in a document can be fixed
+/// in one go.
+pub fn from_value_collect<T: Deserialize>(v: &Value) -> Result<T, Vec<DetailedError>> {
+    FromValueOptions::new().from_value_collect(v)
+}
+
+#[derive(Copy, Clone)]
+enum Segment<'a> {
+    Index(usize),
+    Key(&'a str),
+}
+
+// Seq and Map frames remember the length of the path leading to their
+// container so that it can be restored once their children have been popped.
+enum Event<'a> {
+    Visitor(Option<Segment<'a>>, &'a Value, &'a mut dyn Visitor),
+    Seq(usize, Enumerate<slice::Iter<'a, Value>>, Box<dyn Seq>),
+    Map(
+        usize,
+        &'a Object,
+        btree_map::Iter<'a, String, Value>,
+        Box<dyn Map>,
+    ),
+}
+
+struct Deserializer<'a, 'r> {
+    options: &'r FromValueOptions,
+    path: Vec<Segment<'a>>,
+    stack: Vec<Event<'a>>,
+    nodes: usize,
+    // Pointers to the keys the target ignored, if requested.
+    ignored: Option<&'r mut Vec<String>>,
+    // Errors so far, if accumulating them rather than stopping at the first.
+    errors: Option<&'r mut Vec<DetailedError>>,
+}
+
+impl<'a, 'r> Deserializer<'a, 'r> {
+    fn push(
+        &mut self,
+        segment: Option<Segment<'a>>,
+        v: &'a Value,
+        visitor: &'a mut dyn Visitor,
+    ) -> Result<(), DetailedError> {
+        self.nodes += 1;
+        if let Some(limit) = self.options.max_total_nodes {
+            if self.nodes > limit {
+                return Err(self.limit_exceeded(segment, callback(v), Limit::TotalNodes(limit)));
+            }
+        }
+        self.stack.push(Event::Visitor(segment, v, visitor));
+        Ok(())
+    }
+
+    fn visit(&mut self, v: &'a Value, visitor: &'a mut dyn Visitor) -> Result<(), DetailedError> {
+        let result = match v {
+            Value::Null => visitor.null(),
+            Value::Bool(b) => visitor.boolean(*b),
+            Value::String(ref s) => {
+                self.check_len(None, s, Callback::String)?;
+                visitor.string(s)
+            }
+            Value::Number(Number::U64(n)) => visitor.nonnegative(*n),
+            Value::Number(Number::I64(n)) => visitor.negative(*n),
+            Value::Number(Number::F64(n)) => visitor.float(*n),
+            Value::Array(a) => {
+                self.check_collection(a.len(), Callback::Seq)?;
+                let depth = self.path.len();
+                visitor.seq().map(|seq| {
+                    self.stack.push(Event::Seq(
+                        depth,
+                        a.iter().enumerate(),
+                        careful!(seq as Box<dyn Seq>),
+                    ))
+                })
+            }
+            Value::Object(o) => {
+                self.check_collection(o.len(), Callback::Map)?;
+                let depth = self.path.len();
+                visitor.map().map(|map| {
+                    self.stack.push(Event::Map(
+                        depth,
+                        o,
+                        o.iter(),
+                        careful!(map as Box<dyn Map>),
+                    ))
+                })
+            }
+        };
+        let result = match result {
+            Err(_) if self.options.coerce => coerce(v, visitor),
+            result => result,
+        };
+        result.map_err(|_| self.error(None, callback(v), invalid_type(v)))
+    }
+
+    fn check_len(
+        &self,
+        segment: Option<Segment>,
+        s: &str,
+        callback: Callback,
+    ) -> Result<(), DetailedError> {
+        match self.options.max_string_len {
+            Some(limit) if s.len() > limit => {
+                Err(self.limit_exceeded(segment, callback, Limit::StringLen(limit)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_collection(&self, len: usize, callback: Callback) -> Result<(), DetailedError> {
+        if let Some(limit) = self.options.max_depth {
+            if self.path.len() >= limit {
+                return Err(self.limit_exceeded(None, callback, Limit::Depth(limit)));
+            }
+        }
+        match self.options.max_collection_len {
+            Some(limit) if len > limit => {
+                Err(self.limit_exceeded(None, callback, Limit::CollectionLen(limit)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Record `err` and carry on if accumulating errors, otherwise stop.
+    fn fail(&mut self, err: DetailedError) -> Result<(), DetailedError> {
+        match &mut self.errors {
+            Some(errors) => match err.kind() {
+                ErrorKind::LimitExceeded(_) => Err(err),
+                _ => {
+                    errors.push(err);
+                    Ok(())
+                }
+            },
+            None => Err(err),
+        }
+    }
+
+    // A container that lost some of its children to earlier errors is likely
+    // to fail in `finish` because of them, but may also be missing a field of
+    // its own, so the error is kept and marked as such.
+    fn finish_failed(&mut self, callback: Callback, kind: ErrorKind) -> Result<(), DetailedError> {
+        let mut err = self.error(None, callback, kind);
+        if let Some(errors) = &self.errors {
+            let prefix = format!("{}/", err.path());
+            // Children are done by now, so any error among them is the latest.
+            if errors.last().is_some_and(|e| e.path().starts_with(&prefix)) {
+                err = err.following_child_error();
+            }
+        }
+        self.fail(err)
+    }
+
+    fn limit_exceeded(
+        &self,
+        segment: Option<Segment>,
+        callback: Callback,
+        limit: Limit,
+    ) -> DetailedError {
+        self.error(segment, callback, ErrorKind::LimitExceeded(limit))
+    }
+
+    fn error(
+        &self,
+        segment: Option<Segment>,
+        callback: Callback,
+        kind: ErrorKind,
+    ) -> DetailedError {
+        error(&self.path, segment, callback, kind)
+    }
+}
+
+// Offer a rejected scalar to the visitor again in a different form.
+fn coerce(v: &Value, visitor: &mut dyn Visitor) -> Result<(), Error> {
+    match v {
+        Value::String(s) => match s.as_str() {
+            "true" => visitor.boolean(true),
+            "false" => visitor.boolean(false),
+            s => {
+                if let Ok(n) = s.parse() {
+                    visitor.nonnegative(n)
+                } else if let Ok(n) = s.parse() {
+                    visitor.negative(n)
+                } else {
+                    // Strings such as "3.0" or "1e3" may still be meant for
+                    // an integer.
+                    match s.parse::<f64>() {
+                        Ok(n) if n.is_finite() => {
+                            visitor.float(n).or_else(|_| coerce_float(n, visitor))
+                        }
+                        _ => Err(Error),
+                    }
+                }
+            }
+        },
+        Value::Number(Number::U64(n)) => visitor.float(*n as f64),
+        Value::Number(Number::I64(n)) => visitor.float(*n as f64),
+        Value::Number(Number::F64(n)) => coerce_float(*n, visitor),
+        _ => Err(Error),
+    }
+}
+
+// Offer an integral float to the integer callbacks.
+fn coerce_float(n: f64, visitor: &mut dyn Visitor) -> Result<(), Error> {
+    if n.fract() != 0.0 {
+        Err(Error)
+    } else if n >= 0.0 && n < u64::MAX as f64 {
+        visitor.nonnegative(n as u64)
+    } else if n < 0.0 && n >= i64::MIN as f64 {
+        visitor.negative(n as i64)
+    } else {
+        Err(Error)
+    }
+}
+
+fn root_error(v: &Value) -> DetailedError {
+    error(&[], None, callback(v), invalid_type(v))
+}
+
+// Whether `visitor` is the sink miniserde hands out for values it does not
+// care about.
+fn is_ignore(visitor: &dyn Visitor) -> bool {
+    // Neither the address nor the vtable identifies the sink: it is
+    // zero-sized, and its vtable is emitted wherever `ignore()` gets inlined.
+    // It is however the only zero-sized visitor miniserde hands out, while
+    // derived impls always write into a non-empty `Place`. A hand-written
+    // zero-sized visitor is mistaken for it, so this is only consulted when
+    // unknown keys are denied or reported.
+    mem::size_of_val(visitor) == 0
+}
+
+// The visitor callback that `v` is dispatched to.
+fn callback(v: &Value) -> Callback {
+    match v {
+        Value::Null => Callback::Null,
+        Value::Bool(_) => Callback::Boolean,
+        Value::String(_) => Callback::String,
+        Value::Number(Number::U64(_)) => Callback::Nonnegative,
+        Value::Number(Number::I64(_)) => Callback::Negative,
+        Value::Number(Number::F64(_)) => Callback::Float,
+        Value::Array(_) => Callback::Seq,
+        Value::Object(_) => Callback::Map,
+    }
+}
+
+fn invalid_type(v: &Value) -> ErrorKind {
+    ErrorKind::InvalidType(Unexpected::new(v))
+}
+
+fn error(
+    path: &[Segment],
+    last: Option<Segment>,
+    callback: Callback,
+    kind: ErrorKind,
+) -> DetailedError {
+    DetailedError::new(pointer(path, last), callback, kind)
+}
+
+fn pointer(path: &[Segment], last: Option<Segment>) -> String {
+    let mut pointer = String::new();
+    for segment in path.iter().chain(last.as_ref()) {
+        pointer.push('/');
+        match *segment {
+            Segment::Index(i) => write!(pointer, "{}", i).unwrap(),
+            Segment::Key(k) => pointer.push_str(&escape(k)),
+        }
+    }
+    pointer
+}
+
+#[test]
+#[allow(non_local_definitions)]
+fn error_path() {
+    use miniserde::json;
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Config {
+        servers: Vec<Server>,
+    }
+
+    let v: Value =
+        json::from_str(r#"{"servers": [{"host": "a", "port": 80}, {"host": "b", "port": "80"}]}"#)
+            .unwrap();
+    let err = from_value_detailed::<Config>(&v).unwrap_err();
+    assert_eq!(err.path(), "/servers/1/port");
+    assert_eq!(err.callback(), Callback::String);
+    assert_eq!(
+        err.kind(),
+        &ErrorKind::InvalidType(Unexpected::Str("80".into()))
+    );
+    assert_eq!(
+        err.to_string(),
+        "invalid type: string \"80\", rejected by visitor.string at /servers/1/port"
+    );
+
+    let v: Value = json::from_str(r#"{"servers": [{"host": "a/b~"}]}"#).unwrap();
+    let err = from_value_detailed::<Config>(&v).unwrap_err();
+    assert_eq!(err.path(), "/servers/0");
+    assert_eq!(err.callback(), Callback::MapFinish);
+    assert_eq!(err.kind(), &ErrorKind::Incomplete(vec!["host".into()]));
+    assert_eq!(
+        err.to_string(),
+        "incomplete object, a required field may be missing (present keys: \"host\") at /servers/0"
+    );
+
+    let v: Value = json::from_str(r#"{"a/b~": null}"#).unwrap();
+    let err = from_value_detailed::<std::collections::BTreeMap<String, u8>>(&v).unwrap_err();
+    assert_eq!(err.path(), "/a~1b~0");
+    assert_eq!(err.kind(), &ErrorKind::InvalidType(Unexpected::Null));
+
+    let long = Value::String("x".repeat(100));
+    let err = from_value_detailed::<u8>(&long).unwrap_err();
+    let preview = format!("{}...", "x".repeat(32));
+    assert_eq!(
+        err.kind(),
+        &ErrorKind::InvalidType(Unexpected::Str(preview))
+    );
+}
+
+#[test]
+fn limits() {
+    use miniserde::json;
+
+    let v: Value = json::from_str(r#"{"a": [[1, 2], ["xyz"]]}"#).unwrap();
+    type T = std::collections::BTreeMap<String, Vec<Value>>;
+    assert!(FromValueOptions::new()
+        .max_depth(3)
+        .from_value::<T>(&v)
+        .is_ok());
+
+    let err = FromValueOptions::new()
+        .max_depth(2)
+        .from_value::<T>(&v)
+        .unwrap_err();
+    assert_eq!(err.path(), "/a/0");
+    assert_eq!(err.kind(), &ErrorKind::LimitExceeded(Limit::Depth(2)));
+
+    let err = FromValueOptions::new()
+        .max_total_nodes(5)
+        .from_value::<T>(&v)
+        .unwrap_err();
+    assert_eq!(err.path(), "/a/1");
+    assert_eq!(err.to_string(), "limit of 5 values exceeded at /a/1");
+
+    let err = FromValueOptions::new()
+        .max_string_len(2)
+        .from_value::<T>(&v)
+        .unwrap_err();
+    assert_eq!(err.path(), "/a/1/0");
+    assert_eq!(err.kind(), &ErrorKind::LimitExceeded(Limit::StringLen(2)));
+
+    let err = FromValueOptions::new()
+        .max_collection_len(1)
+        .from_value::<T>(&v)
+        .unwrap_err();
+    assert_eq!(err.path(), "/a");
+    assert_eq!(
+        err.kind(),
+        &ErrorKind::LimitExceeded(Limit::CollectionLen(1))
+    );
+}
+
+#[test]
+#[allow(non_local_definitions)]
+fn unknown_keys() {
+    use miniserde::json;
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Server {
+        host: String,
+        port: Option<u16>,
+    }
+
+    let v: Value = json::from_str(r#"[{"host": "a", "port": 80, "extra": {"x": 1}}]"#).unwrap();
+    assert!(from_value_detailed::<Vec<Server>>(&v).is_ok());
+    assert!(FromValueOptions::new()
+        .deny_unknown_keys(true)
+        .from_value::<Vec<Value>>(&v)
+        .is_ok());
+
+    let v: Value = json::from_str(r#"[{"host": "a", "prot": 80}]"#).unwrap();
+    let err = FromValueOptions::new()
+        .deny_unknown_keys(true)
+        .from_value::<Vec<Server>>(&v)
+        .unwrap_err();
+    assert_eq!(err.path(), "/0/prot");
+    assert_eq!(err.kind(), &ErrorKind::UnknownKey);
+    assert_eq!(err.to_string(), "unknown key at /0/prot");
+}
+
+#[test]
+#[allow(non_local_definitions)]
+fn ignored_keys() {
+    use miniserde::json;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Server {
+        host: String,
+    }
+
+    let v: Value = json::from_str(
+        r#"{"servers": [{"host": "a", "prot": 80}, {"host": "b", "old": {"x": 1}}], "a/b": 1}"#,
+    )
+    .unwrap();
+    let (_, ignored) = from_value_report::<Value>(&v).unwrap();
+    assert!(ignored.is_empty());
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Config {
+        servers: Vec<Server>,
+    }
+    let (config, ignored) = from_value_report::<Config>(&v).unwrap();
+    assert_eq!(config.servers[1], Server { host: "b".into() });
+    assert_eq!(ignored, ["/a~1b", "/servers/0/prot", "/servers/1/old"]);
+}
+
+#[test]
+fn zero_sized_key_visitor() {
+    use miniserde::json;
+
+    // Accepts `{"reserved": null}` through a zero-sized key visitor.
+    struct Reserved;
+    struct ReservedMap(NullOnly);
+    struct NullOnly;
+
+    impl Visitor for Reserved {
+        fn map(&mut self) -> Result<Box<dyn Map + '_>, Error> {
+            Ok(Box::new(ReservedMap(NullOnly)))
+        }
+    }
+
+    impl Map for ReservedMap {
+        fn key(&mut self, _k: &str) -> Result<&mut dyn Visitor, Error> {
+            Ok(&mut self.0)
+        }
+
+        fn finish(&mut self) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    impl Visitor for NullOnly {
+        fn null(&mut self) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    let v: Value = json::from_str(r#"{"reserved": 5}"#).unwrap();
+    assert!(crate::drive(&v, &mut Reserved).is_err());
+    let options = FromValueOptions::new().coerce(true).max_depth(8);
+    let err = options.drive(&v, &mut Reserved).unwrap_err();
+    assert_eq!(err.path(), "/reserved");
+    assert_eq!(err.callback(), Callback::Nonnegative);
+
+    let v: Value = json::from_str(r#"{"reserved": null}"#).unwrap();
+    assert!(options.drive(&v, &mut Reserved).is_ok());
+    let err = FromValueOptions::new()
+        .deny_unknown_keys(true)
+        .drive(&v, &mut Reserved)
+        .unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::UnknownKey);
+}
+
+#[test]
+#[allow(non_local_definitions)]
+fn collect_errors() {
+    use miniserde::json;
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    let v: Value = json::from_str(
+        r#"[{"host": "a", "port": "80"}, {"host": "b", "port": 80}, {"host": 1}, {"port": 1}]"#,
+    )
+    .unwrap();
+    let errors = from_value_collect::<Vec<Server>>(&v).unwrap_err();
+    let errors: Vec<_> = errors
+        .iter()
+        .map(|e| (e.path(), e.callback(), e.follows_child_error()))
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            ("/0/port", Callback::String, false),
+            ("/0", Callback::MapFinish, true),
+            ("/2/host", Callback::Nonnegative, false),
+            ("/2", Callback::MapFinish, true),
+            ("/3", Callback::MapFinish, false),
+        ]
+    );
+
+    let v: Value = json::from_str(r#"[{"host": "b", "port": 80}]"#).unwrap();
+    assert_eq!(from_value_collect::<Vec<Server>>(&v).unwrap().len(), 1);
+
+    // The missing field is reported next to the errors of its siblings.
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct S {
+        a: u8,
+        b: u8,
+    }
+    let v: Value = json::from_str(r#"{"a": "x", "c": 1}"#).unwrap();
+    let errors = FromValueOptions::new()
+        .deny_unknown_keys(true)
+        .from_value_collect::<S>(&v)
+        .unwrap_err();
+    let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        [
+            "invalid type: string \"x\", rejected by visitor.string at /a",
+            "unknown key at /c",
+            "incomplete object, a required field may be missing (present keys: \"a\", \"c\"), \
+             possibly because of an error inside it, at the root",
+        ]
+    );
+    assert_eq!(
+        errors[2].kind(),
+        &ErrorKind::Incomplete(vec!["a".into(), "c".into()])
+    );
+}
+
+#[test]
+#[allow(non_local_definitions)]
+fn coercion() {
+    use miniserde::json;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Config {
+        port: u16,
+        offset: i32,
+        ratio: f64,
+        count: u8,
+        enabled: bool,
+        name: String,
+    }
+
+    let v: Value = json::from_str(
+        r#"{"port": "8080", "offset": "-3", "ratio": "0.5", "count": 3.0, "enabled": "true", "name": "1"}"#,
+    )
+    .unwrap();
+    assert!(from_value_detailed::<Config>(&v).is_err());
+    let config = FromValueOptions::new()
+        .coerce(true)
+        .from_value::<Config>(&v)
+        .unwrap();
+    assert_eq!(
+        config,
+        Config {
+            port: 8080,
+            offset: -3,
+            ratio: 0.5,
+            count: 3,
+            enabled: true,
+            name: "1".into(),
+        }
+    );
+
+    let v: Value = json::from_str(r#"["3.0", "1e3", 4.0]"#).unwrap();
+    let options = FromValueOptions::new().coerce(true);
+    assert_eq!(options.from_value::<Vec<u16>>(&v).unwrap(), [3, 1000, 4]);
+    let v: Value = json::from_str(r#"["-2.0", "-1e1"]"#).unwrap();
+    assert_eq!(options.from_value::<Vec<i8>>(&v).unwrap(), [-2, -10]);
+
+    let v: Value = json::from_str(r#"["x", 1.5, "inf", "2.5", "1e3"]"#).unwrap();
+    let errors = FromValueOptions::new()
+        .coerce(true)
+        .from_value_collect::<Vec<u8>>(&v)
+        .unwrap_err();
+    assert_eq!(errors.len(), 5);
+    assert_eq!(
+        errors[0].kind(),
+        &ErrorKind::InvalidType(Unexpected::Str("x".into()))
+    );
+}
@@ -0,0 +1,176 @@
+use crate::eq::equal;
+use crate::pointer::escape;
+use miniserde::json::{self, Value};
+use std::fmt::{self, Display};
+
+/// A difference between two Values, found by `diff`.
+#[derive(Clone, Debug)]
+pub struct Change<'a> {
+    /// JSON Pointer to the value that differs.
+    pub path: String,
+    /// How the value differs.
+    pub kind: ChangeKind<'a>,
+}
+
+/// What happened to the value at a `Change`'s path.
+#[derive(Clone, Debug)]
+pub enum ChangeKind<'a> {
+    /// Only present in the new Value.
+    Added(&'a Value),
+    /// Only present in the old Value.
+    Removed(&'a Value),
+    /// Present in both with different contents, the old one first. Objects
+    /// and arrays are only reported as changed when they replace a value of
+    /// another type.
+    Changed(&'a Value, &'a Value),
+}
+
+/// Compare two Values and list their differences in document order.
+///
+/// Object members are matched by key and array elements by index, so an
+/// element inserted in the middle of an array changes every following one.
+/// Numbers compare by value, so `1` and `1.0` are equal. Nesting depth is not
+/// limited by the call stack.
+pub fn diff<'a>(old: &'a Value, new: &'a Value) -> Vec<Change<'a>> {
+    let mut changes = Vec::new();
+    let mut stack = vec![Event::Compare(String::new(), old, new)];
+    while let Some(event) = stack.pop() {
+        let (path, old, new) = match event {
+            Event::Compare(path, old, new) => (path, old, new),
+            Event::Change(change) => {
+                changes.push(change);
+                continue;
+            }
+        };
+        match (old, new) {
+            (Value::Object(old), Value::Object(new)) => {
+                let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+                keys.sort();
+                keys.dedup();
+                for k in keys.into_iter().rev() {
+                    let path = format!("{}/{}", path, escape(k));
+                    stack.push(match (old.get(k), new.get(k)) {
+                        (Some(old), Some(new)) => Event::Compare(path, old, new),
+                        (Some(old), None) => Event::change(path, ChangeKind::Removed(old)),
+                        (None, Some(new)) => Event::change(path, ChangeKind::Added(new)),
+                        (None, None) => unreachable!(),
+                    });
+                }
+            }
+            (Value::Array(old), Value::Array(new)) => {
+                for i in (0..old.len().max(new.len())).rev() {
+                    let path = format!("{}/{}", path, i);
+                    stack.push(match (old.get(i), new.get(i)) {
+                        (Some(old), Some(new)) => Event::Compare(path, old, new),
+                        (Some(old), None) => Event::change(path, ChangeKind::Removed(old)),
+                        (None, Some(new)) => Event::change(path, ChangeKind::Added(new)),
+                        (None, None) => unreachable!(),
+                    });
+                }
+            }
+            (old, new) if equal(old, new) => {}
+            (old, new) => changes.push(Change {
+                path,
+                kind: ChangeKind::Changed(old, new),
+            }),
+        }
+    }
+    changes
+}
+
+enum Event<'a> {
+    Compare(String, &'a Value, &'a Value),
+    Change(Change<'a>),
+}
+
+impl<'a> Event<'a> {
+    fn change(path: String, kind: ChangeKind<'a>) -> Self {
+        Event::Change(Change { path, kind })
+    }
+}
+
+/// Render changes in a unified format: a `-` line with the old value and a
+/// `+` line with the new one, each followed by the path and the compact
+/// JSON. With `color`, removed lines are red and added lines green, using
+/// ANSI escape codes.
+pub fn render_diff(changes: &[Change], color: bool) -> String {
+    let mut out = String::new();
+    for change in changes {
+        let (old, new) = match change.kind {
+            ChangeKind::Added(new) => (None, Some(new)),
+            ChangeKind::Removed(old) => (Some(old), None),
+            ChangeKind::Changed(old, new) => (Some(old), Some(new)),
+        };
+        for (sign, v, ansi) in [('-', old, "\x1b[31m"), ('+', new, "\x1b[32m")] {
+            if let Some(v) = v {
+                let line = format!("{} {}: {}", sign, change.path, json::to_string(v));
+                if color {
+                    out.push_str(ansi);
+                    out.push_str(&line);
+                    out.push_str("\x1b[0m\n");
+                } else {
+                    out.push_str(&line);
+                    out.push('\n');
+                }
+            }
+        }
+    }
+    out
+}
+
+impl<'a> Display for Change<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let rendered = render_diff(std::slice::from_ref(self), false);
+        formatter.write_str(rendered.trim_end())
+    }
+}
+
+#[test]
+fn changes() {
+    let old: Value = json::from_str(
+        r#"{"a": [1, 2, 3], "b": {"c": 1, "d/e": 2}, "f": 1, "g": [1], "h": {"i": null}}"#,
+    )
+    .unwrap();
+    let new: Value = json::from_str(
+        r#"{"a": [1, 5], "b": {"c": 1.0}, "f": "x", "g": [1, {}], "h": [], "j": true}"#,
+    )
+    .unwrap();
+    let changes = diff(&old, &new);
+    let expected = "\
+- /a/1: 2
++ /a/1: 5
+- /a/2: 3
+- /b/d~1e: 2
+- /f: 1
++ /f: \"x\"
++ /g/1: {}
+- /h: {\"i\":null}
++ /h: []
++ /j: true
+";
+    assert_eq!(render_diff(&changes, false), expected);
+    assert_eq!(changes[0].to_string(), "- /a/1: 2\n+ /a/1: 5");
+    assert_eq!(
+        render_diff(&changes[1..2], true),
+        "\x1b[31m- /a/2: 3\x1b[0m\n"
+    );
+    assert!(diff(&old, &old).is_empty());
+}
+
+#[test]
+fn deep() {
+    use miniserde::json::Array;
+
+    let mut old = Value::Bool(true);
+    let mut new = Value::Bool(false);
+    for _ in 0..10_000 {
+        let (mut a, mut b) = (Array::new(), Array::new());
+        a.push(old);
+        b.push(new);
+        old = Value::Array(a);
+        new = Value::Array(b);
+    }
+    let changes = diff(&old, &new);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].path, "/0".repeat(10_000));
+}
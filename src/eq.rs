@@ -0,0 +1,74 @@
+use miniserde::json::{Number, Value};
+
+// `Value` does not implement `PartialEq`, and a derived implementation would
+// recurse anyway. Numbers compare by numeric value, so `1`, `1.0` and a
+// non-negative `Number::I64(1)` are all equal.
+pub(crate) fn equal(a: &Value, b: &Value) -> bool {
+    let mut stack = vec![(a, b)];
+    while let Some(pair) = stack.pop() {
+        match pair {
+            (Value::Null, Value::Null) => {}
+            (Value::Bool(a), Value::Bool(b)) if a == b => {}
+            (Value::String(a), Value::String(b)) if a == b => {}
+            (Value::Number(a), Value::Number(b)) if number_equal(a, b) => {}
+            (Value::Array(a), Value::Array(b)) if a.len() == b.len() => {
+                stack.extend(a.iter().zip(b.iter()));
+            }
+            (Value::Object(a), Value::Object(b)) if a.len() == b.len() => {
+                for ((ka, va), (kb, vb)) in a.iter().zip(b.iter()) {
+                    if ka != kb {
+                        return false;
+                    }
+                    stack.push((va, vb));
+                }
+            }
+            _ => return false,
+        }
+    }
+    true
+}
+
+fn number_equal(a: &Number, b: &Number) -> bool {
+    match (a, b) {
+        (Number::U64(a), Number::U64(b)) => a == b,
+        (Number::I64(a), Number::I64(b)) => a == b,
+        (Number::F64(a), Number::F64(b)) => a == b,
+        (Number::U64(u), Number::I64(i)) | (Number::I64(i), Number::U64(u)) => {
+            *i >= 0 && *i as u64 == *u
+        }
+        (Number::F64(f), Number::U64(u)) | (Number::U64(u), Number::F64(f)) => {
+            f.fract() == 0.0 && *f == *u as f64
+        }
+        (Number::F64(f), Number::I64(i)) | (Number::I64(i), Number::F64(f)) => {
+            f.fract() == 0.0 && *f == *i as f64
+        }
+    }
+}
+
+#[test]
+fn equality() {
+    use miniserde::json;
+
+    let a: Value = json::from_str(r#"{"a": [1, 2.0, {"b": null}], "c": "d"}"#).unwrap();
+    let b: Value = json::from_str(r#"{"c": "d", "a": [1.0, 2, {"b": null}]}"#).unwrap();
+    assert!(equal(&a, &b));
+    for other in [
+        r#"{"a": [1, 2, {"b": false}], "c": "d"}"#,
+        r#"{"a": [1, 2, {"b": null}], "c": "e"}"#,
+        r#"{"a": [1, 2], "c": "d"}"#,
+        r#"{"a": [1, 2, {"x": null}], "c": "d"}"#,
+        r#"{"a": [1, 2.5, {"b": null}], "c": "d"}"#,
+        r#"[1, 2]"#,
+    ] {
+        let b: Value = json::from_str(other).unwrap();
+        assert!(!equal(&a, &b), "{}", other);
+    }
+    assert!(equal(
+        &Value::Number(Number::I64(3)),
+        &Value::Number(Number::U64(3))
+    ));
+    assert!(!equal(
+        &Value::Number(Number::I64(-3)),
+        &Value::Number(Number::F64(3.0))
+    ));
+}
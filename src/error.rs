@@ -0,0 +1,235 @@
+use miniserde::json::{Number, Value};
+use std::fmt::{self, Display};
+
+/// The visitor callback that rejected its input.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Callback {
+    Null,
+    Boolean,
+    String,
+    Negative,
+    Nonnegative,
+    Float,
+    Seq,
+    Map,
+    Element,
+    Key,
+    SeqFinish,
+    MapFinish,
+}
+
+impl Callback {
+    fn as_str(self) -> &'static str {
+        match self {
+            Callback::Null => "visitor.null",
+            Callback::Boolean => "visitor.boolean",
+            Callback::String => "visitor.string",
+            Callback::Negative => "visitor.negative",
+            Callback::Nonnegative => "visitor.nonnegative",
+            Callback::Float => "visitor.float",
+            Callback::Seq => "visitor.seq",
+            Callback::Map => "visitor.map",
+            Callback::Element => "seq.element",
+            Callback::Key => "map.key",
+            Callback::SeqFinish => "seq.finish",
+            Callback::MapFinish => "map.finish",
+        }
+    }
+}
+
+impl Display for Callback {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+/// Input that a visitor callback rejected.
+///
+/// Strings are truncated to a short preview so that errors about large
+/// documents stay readable.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Unexpected {
+    Null,
+    Bool(bool),
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+    Str(String),
+    Array(usize),
+    Object(usize),
+}
+
+const PREVIEW_LEN: usize = 32;
+
+impl Unexpected {
+    pub(crate) fn new(v: &Value) -> Self {
+        match v {
+            Value::Null => Unexpected::Null,
+            Value::Bool(b) => Unexpected::Bool(*b),
+            Value::Number(Number::U64(n)) => Unexpected::Unsigned(*n),
+            Value::Number(Number::I64(n)) => Unexpected::Signed(*n),
+            Value::Number(Number::F64(n)) => Unexpected::Float(*n),
+            Value::String(s) => match s.char_indices().nth(PREVIEW_LEN) {
+                Some((end, _)) => Unexpected::Str(format!("{}...", &s[..end])),
+                None => Unexpected::Str(s.clone()),
+            },
+            Value::Array(a) => Unexpected::Array(a.len()),
+            Value::Object(o) => Unexpected::Object(o.len()),
+        }
+    }
+}
+
+impl Display for Unexpected {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Unexpected::Null => formatter.write_str("null"),
+            Unexpected::Bool(b) => write!(formatter, "boolean `{}`", b),
+            Unexpected::Unsigned(n) => write!(formatter, "integer `{}`", n),
+            Unexpected::Signed(n) => write!(formatter, "integer `{}`", n),
+            Unexpected::Float(n) => write!(formatter, "floating point `{}`", n),
+            Unexpected::Str(s) => write!(formatter, "string {:?}", s),
+            Unexpected::Array(len) => write!(formatter, "array of length {}", len),
+            Unexpected::Object(len) => write!(formatter, "object with {} keys", len),
+        }
+    }
+}
+
+/// A limit configured in `FromValueOptions`, along with its value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Limit {
+    Depth(usize),
+    TotalNodes(usize),
+    StringLen(usize),
+    CollectionLen(usize),
+}
+
+impl Display for Limit {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Limit::Depth(n) => write!(formatter, "depth limit of {}", n),
+            Limit::TotalNodes(n) => write!(formatter, "limit of {} values", n),
+            Limit::StringLen(n) => write!(formatter, "string length limit of {}", n),
+            Limit::CollectionLen(n) => write!(formatter, "collection length limit of {}", n),
+        }
+    }
+}
+
+/// What went wrong, as far as it can be told from the outside.
+///
+/// Miniserde visitors do not say what they expected, so only the rejected
+/// input can be described.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ErrorKind {
+    /// A visitor callback refused a value of this type.
+    InvalidType(Unexpected),
+    /// `Map::finish` failed on an object with these keys. For derived structs
+    /// this means a required field was missing.
+    Incomplete(Vec<String>),
+    /// The target type ignores this object key. Only reported when
+    /// `FromValueOptions::deny_unknown_keys` is set.
+    UnknownKey,
+    /// Any other `Seq` or `Map` callback failed.
+    Rejected,
+    /// The input is larger than allowed. The callback is the one that would
+    /// have been invoked next.
+    LimitExceeded(Limit),
+}
+
+/// Error returned by `from_value_detailed` and `FromValueOptions`.
+///
+/// Unlike `miniserde::Error`, this records where in the input the failure
+/// happened, as a JSON Pointer, and which callback reported it.
+#[derive(Clone, Debug)]
+pub struct DetailedError {
+    path: String,
+    callback: Callback,
+    kind: ErrorKind,
+    follows_child_error: bool,
+}
+
+impl DetailedError {
+    pub(crate) fn new(path: String, callback: Callback, kind: ErrorKind) -> Self {
+        DetailedError {
+            path,
+            callback,
+            kind,
+            follows_child_error: false,
+        }
+    }
+
+    pub(crate) fn following_child_error(mut self) -> Self {
+        self.follows_child_error = true;
+        self
+    }
+
+    pub(crate) fn with_prefix(mut self, prefix: &str) -> Self {
+        self.path.insert_str(0, prefix);
+        self
+    }
+
+    /// JSON Pointer to the node being processed when the error occurred. The
+    /// empty string designates the root.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Visitor callback that failed.
+    pub fn callback(&self) -> Callback {
+        self.callback
+    }
+
+    /// Description of the failure.
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// Whether this is the failure of a container's `finish` after an error
+    /// was reported inside the container, which may have caused it. Only
+    /// set by `from_value_collect`.
+    pub fn follows_child_error(&self) -> bool {
+        self.follows_child_error
+    }
+}
+
+impl Display for DetailedError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ErrorKind::InvalidType(unexpected) => write!(
+                formatter,
+                "invalid type: {}, rejected by {}",
+                unexpected, self.callback
+            )?,
+            ErrorKind::Incomplete(keys) => {
+                formatter.write_str("incomplete object, a required field may be missing")?;
+                for (i, key) in keys.iter().enumerate() {
+                    let sep = if i == 0 { " (present keys: " } else { ", " };
+                    write!(formatter, "{}{:?}", sep, key)?;
+                }
+                if keys.is_empty() {
+                    formatter.write_str(" (no keys present)")?;
+                } else {
+                    formatter.write_str(")")?;
+                }
+            }
+            ErrorKind::UnknownKey => formatter.write_str("unknown key")?,
+            ErrorKind::Rejected => write!(formatter, "{} failed", self.callback)?,
+            ErrorKind::LimitExceeded(limit) => write!(formatter, "{} exceeded", limit)?,
+        }
+        if self.follows_child_error {
+            formatter.write_str(", possibly because of an error inside it,")?;
+        }
+        if self.path.is_empty() {
+            formatter.write_str(" at the root")
+        } else {
+            write!(formatter, " at {}", self.path)
+        }
+    }
+}
+
+impl std::error::Error for DetailedError {}
+
+impl From<DetailedError> for miniserde::Error {
+    fn from(_: DetailedError) -> Self {
+        miniserde::Error
+    }
+}
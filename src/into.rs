@@ -0,0 +1,180 @@
+use crate::ser::to_value;
+use miniserde::{
+    json::{Array, Number, Object, Value},
+    Serialize,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+
+/// Conversion into a `Value`, used for the expressions interpolated by
+/// `value!`.
+///
+/// `From<T> for Value` cannot be written outside of miniserde, so this trait
+/// stands in for it. Owned integers, floats, strings, `Option`, `Vec`,
+/// `BTreeMap`, `HashMap` and tuples are converted directly, without going
+/// through `Serialize`. Any other `Serialize` type converts by reference,
+/// as in `value!({"config": &config})`: a blanket impl over owned
+/// `Serialize` types would overlap with the direct ones.
+///
+/// Non-negative signed integers become `Number::U64`, as they would when
+/// parsed from JSON text.
+pub trait IntoValue {
+    fn into_value(self) -> Value;
+}
+
+impl<T: ?Sized + Serialize> IntoValue for &T {
+    fn into_value(self) -> Value {
+        to_value(self)
+    }
+}
+
+impl IntoValue for Value {
+    fn into_value(self) -> Value {
+        self
+    }
+}
+
+impl IntoValue for bool {
+    fn into_value(self) -> Value {
+        Value::Bool(self)
+    }
+}
+
+macro_rules! unsigned {
+    ($($ty:ty)*) => {
+        $(
+            impl IntoValue for $ty {
+                fn into_value(self) -> Value {
+                    Value::Number(Number::U64(self as u64))
+                }
+            }
+        )*
+    };
+}
+
+unsigned!(u8 u16 u32 u64 usize);
+
+macro_rules! signed {
+    ($($ty:ty)*) => {
+        $(
+            impl IntoValue for $ty {
+                fn into_value(self) -> Value {
+                    if self < 0 {
+                        Value::Number(Number::I64(self as i64))
+                    } else {
+                        Value::Number(Number::U64(self as u64))
+                    }
+                }
+            }
+        )*
+    };
+}
+
+signed!(i8 i16 i32 i64 isize);
+
+impl IntoValue for f32 {
+    fn into_value(self) -> Value {
+        Value::Number(Number::F64(self as f64))
+    }
+}
+
+impl IntoValue for f64 {
+    fn into_value(self) -> Value {
+        Value::Number(Number::F64(self))
+    }
+}
+
+impl IntoValue for String {
+    fn into_value(self) -> Value {
+        Value::String(self)
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self) -> Value {
+        match self {
+            Some(v) => v.into_value(),
+            None => Value::Null,
+        }
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(self) -> Value {
+        Value::Array(self.into_iter().map(T::into_value).collect::<Array>())
+    }
+}
+
+impl<K: ToString, V: IntoValue> IntoValue for BTreeMap<K, V> {
+    fn into_value(self) -> Value {
+        object(self)
+    }
+}
+
+impl<K: ToString, V: IntoValue, H: BuildHasher> IntoValue for HashMap<K, V, H> {
+    fn into_value(self) -> Value {
+        object(self)
+    }
+}
+
+fn object<K: ToString, V: IntoValue>(map: impl IntoIterator<Item = (K, V)>) -> Value {
+    let members = map
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.into_value()));
+    Value::Object(members.collect::<Object>())
+}
+
+// Tuples become arrays.
+macro_rules! tuple {
+    ($($name:ident)+) => {
+        impl<$($name: IntoValue),+> IntoValue for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn into_value(self) -> Value {
+                let ($($name,)+) = self;
+                let mut array = Array::new();
+                $(array.push($name.into_value());)+
+                Value::Array(array)
+            }
+        }
+    };
+}
+
+tuple!(A);
+tuple!(A B);
+tuple!(A B C);
+tuple!(A B C D);
+tuple!(A B C D E);
+tuple!(A B C D E F);
+tuple!(A B C D E F G);
+tuple!(A B C D E F G H);
+
+#[test]
+fn std_types() {
+    use crate::eq::equal;
+    use miniserde::json;
+
+    let mut hash = HashMap::new();
+    hash.insert(1, vec![Some(-1i8), None]);
+    let mut btree = BTreeMap::new();
+    btree.insert("a".to_owned(), (1u8, "x", 2.5f32, ("y".to_owned(),)));
+    let v = (
+        hash,
+        btree,
+        "s".to_owned(),
+        true,
+        -3i64,
+        3i64,
+        &[1u8, 2][..],
+    )
+        .into_value();
+    let expected: Value = json::from_str(
+        r#"[{"1": [-1, null]}, {"a": [1, "x", 2.5, ["y"]]}, "s", true, -3, 3, [1, 2]]"#,
+    )
+    .unwrap();
+    assert!(equal(&v, &expected), "{}", json::to_string(&v));
+
+    // Same representation as parsed text, so integer visitors accept it.
+    let n: u32 = crate::from_value(&7i32.into_value()).unwrap();
+    assert_eq!(n, 7);
+    assert!(equal(&expected.clone().into_value(), &expected));
+}
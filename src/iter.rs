@@ -0,0 +1,74 @@
+use crate::ValueDeserializer;
+use miniserde::{json::Value, Deserialize, Error, Result};
+use std::marker::PhantomData;
+use std::slice;
+
+/// Iterate over the elements of a JSON array, deserializing each one only
+/// when it is reached.
+///
+/// A bad element only fails its own item, so callers can skip it or stop
+/// early. If `v` is not an array, the iterator yields a single error.
+pub fn from_value_iter<T: Deserialize>(v: &Value) -> FromValueIter<'_, T> {
+    let (elements, invalid) = match v {
+        Value::Array(a) => (a.iter(), false),
+        _ => ([].iter(), true),
+    };
+    FromValueIter {
+        elements,
+        invalid,
+        de: ValueDeserializer::new(),
+        marker: PhantomData,
+    }
+}
+
+/// Iterator returned by `from_value_iter`.
+pub struct FromValueIter<'a, T> {
+    elements: slice::Iter<'a, Value>,
+    // The input was not an array, which is reported by the first item.
+    invalid: bool,
+    de: ValueDeserializer,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: Deserialize> Iterator for FromValueIter<'a, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.invalid {
+            self.invalid = false;
+            return Some(Err(Error));
+        }
+        let v = self.elements.next()?;
+        Some(self.de.deserialize(v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.elements.len() + self.invalid as usize;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T: Deserialize> ExactSizeIterator for FromValueIter<'a, T> {}
+
+#[test]
+fn lazy() {
+    let v: Value = miniserde::json::from_str(r#"[1, "x", 3, 4]"#).unwrap();
+    let mut iter = from_value_iter::<u8>(&v);
+    assert_eq!(iter.len(), 4);
+    assert_eq!(iter.next().unwrap().unwrap(), 1);
+    assert!(iter.next().unwrap().is_err());
+    assert_eq!(iter.len(), 2);
+    let rest: Vec<u8> = iter.map(Result::unwrap).collect();
+    assert_eq!(rest, [3, 4]);
+
+    let v = Value::Bool(true);
+    let items: Vec<_> = from_value_iter::<bool>(&v).collect();
+    assert_eq!(items.len(), 1);
+    assert!(items[0].is_err());
+}
+
+#[test]
+fn send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<FromValueIter<'static, String>>();
+}
@@ -0,0 +1,829 @@
+use crate::de::from_value_detailed;
+use crate::eq::equal;
+use crate::error::DetailedError;
+use miniserde::{
+    json::{Number, Value},
+    Deserialize,
+};
+use std::fmt::{self, Display};
+
+/// A parsed JSONPath expression, such as `$.items[?(@.price < 10)].name`.
+///
+/// Supports the RFC 9535 name, wildcard, index, slice and filter selectors,
+/// both as child and descendant (`..`) segments. Filters may combine
+/// existence tests and comparisons with `&&`, `||`, `!` and parentheses;
+/// function extensions are not supported. As in the RFC, queries compared
+/// in a filter must be singular, made only of name and index selectors.
+/// Filters, negations and parentheses may nest at most 128 levels deep.
+#[derive(Clone, Debug)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+/// Error parsing a JSONPath expression.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonPathError {
+    offset: usize,
+    expected: &'static str,
+}
+
+impl JsonPathError {
+    /// Byte offset in the expression where parsing failed.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl Display for JsonPathError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "invalid JSONPath: expected {} at offset {}",
+            self.expected, self.offset,
+        )
+    }
+}
+
+impl std::error::Error for JsonPathError {}
+
+/// Error returned by `from_value_query`.
+#[derive(Clone, Debug)]
+pub enum FromValueQueryError {
+    /// The JSONPath expression could not be parsed.
+    Syntax(JsonPathError),
+    /// The match with this index could not be deserialized. The error's path
+    /// is relative to the match.
+    Deserialize(usize, DetailedError),
+}
+
+impl Display for FromValueQueryError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FromValueQueryError::Syntax(err) => Display::fmt(err, formatter),
+            FromValueQueryError::Deserialize(i, err) => write!(formatter, "match {}: {}", i, err),
+        }
+    }
+}
+
+impl std::error::Error for FromValueQueryError {}
+
+/// Evaluate a JSONPath expression against `v` and return the matches in
+/// document order.
+pub fn query<'a>(v: &'a Value, path: &str) -> Result<Vec<&'a Value>, JsonPathError> {
+    Ok(JsonPath::parse(path)?.query(v))
+}
+
+/// Evaluate a JSONPath expression against `v` and deserialize every match.
+pub fn from_value_query<T: Deserialize>(
+    v: &Value,
+    path: &str,
+) -> Result<Vec<T>, FromValueQueryError> {
+    let path = JsonPath::parse(path).map_err(FromValueQueryError::Syntax)?;
+    path.query(v)
+        .into_iter()
+        .enumerate()
+        .map(|(i, v)| {
+            from_value_detailed(v).map_err(|err| FromValueQueryError::Deserialize(i, err))
+        })
+        .collect()
+}
+
+impl JsonPath {
+    /// Parse an expression, which must start with `$`.
+    pub fn parse(path: &str) -> Result<Self, JsonPathError> {
+        let mut parser = Parser {
+            input: path,
+            pos: 0,
+            depth: 0,
+        };
+        parser.expect('$', "'$'")?;
+        let segments = parser.segments()?;
+        if parser.pos < path.len() {
+            return Err(parser.error("end of path"));
+        }
+        Ok(JsonPath { segments })
+    }
+
+    /// Return the values matched in `v`, in document order.
+    pub fn query<'a>(&self, v: &'a Value) -> Vec<&'a Value> {
+        select(&self.segments, v, v)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Segment {
+    // `..` segment, applied to the node and all its descendants.
+    descendant: bool,
+    selectors: Vec<Selector>,
+}
+
+#[derive(Clone, Debug)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Filter(Expr),
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Or(Vec<Expr>),
+    And(Vec<Expr>),
+    Not(Box<Expr>),
+    Exists(Query),
+    Compare(Operand, Op, Operand),
+}
+
+#[derive(Clone, Debug)]
+enum Operand {
+    Literal(Value),
+    Query(Query),
+}
+
+#[derive(Clone, Debug)]
+struct Query {
+    // Starts at `@` rather than `$`.
+    relative: bool,
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+// Apply segments one after the other. Descendants are walked with an
+// explicit stack, so deep documents cannot overflow the call stack.
+fn select<'a>(segments: &[Segment], node: &'a Value, root: &'a Value) -> Vec<&'a Value> {
+    let mut nodes = vec![node];
+    for segment in segments {
+        let mut next = Vec::new();
+        for node in nodes {
+            if !segment.descendant {
+                segment.apply(node, root, &mut next);
+                continue;
+            }
+            let mut stack = vec![node];
+            while let Some(node) = stack.pop() {
+                segment.apply(node, root, &mut next);
+                match node {
+                    Value::Array(a) => stack.extend(a.iter().rev()),
+                    Value::Object(o) => stack.extend(o.values().rev()),
+                    _ => {}
+                }
+            }
+        }
+        nodes = next;
+    }
+    nodes
+}
+
+impl Segment {
+    fn apply<'a>(&self, node: &'a Value, root: &'a Value, out: &mut Vec<&'a Value>) {
+        for selector in &self.selectors {
+            selector.apply(node, root, out);
+        }
+    }
+}
+
+impl Selector {
+    fn apply<'a>(&self, node: &'a Value, root: &'a Value, out: &mut Vec<&'a Value>) {
+        match (self, node) {
+            (Selector::Name(name), Value::Object(o)) => out.extend(o.get(name)),
+            (Selector::Wildcard, Value::Object(o)) => out.extend(o.values()),
+            (Selector::Wildcard, Value::Array(a)) => out.extend(a.iter()),
+            (Selector::Index(i), Value::Array(a)) => {
+                let i = normalize(*i, a.len() as i64);
+                if i >= 0 {
+                    out.extend(a.get(i as usize));
+                }
+            }
+            (Selector::Slice(start, end, step), Value::Array(a)) => {
+                let len = a.len() as i64;
+                let step = step.unwrap_or(1);
+                if step > 0 {
+                    let mut i = normalize(start.unwrap_or(0), len).clamp(0, len);
+                    let upper = normalize(end.unwrap_or(len), len).clamp(0, len);
+                    while i < upper {
+                        out.push(&a[i as usize]);
+                        i = match i.checked_add(step) {
+                            Some(i) => i,
+                            None => break,
+                        };
+                    }
+                } else if step < 0 {
+                    let mut i = normalize(start.unwrap_or(len - 1), len).clamp(-1, len - 1);
+                    let lower = normalize(end.unwrap_or(-len - 1), len).clamp(-1, len - 1);
+                    while lower < i {
+                        out.push(&a[i as usize]);
+                        i = match i.checked_add(step) {
+                            Some(i) => i,
+                            None => break,
+                        };
+                    }
+                }
+            }
+            (Selector::Filter(expr), Value::Object(o)) => {
+                out.extend(o.values().filter(|v| expr.test(v, root)));
+            }
+            (Selector::Filter(expr), Value::Array(a)) => {
+                out.extend(a.iter().filter(|v| expr.test(v, root)));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn normalize(i: i64, len: i64) -> i64 {
+    if i < 0 {
+        len + i
+    } else {
+        i
+    }
+}
+
+impl Expr {
+    fn test(&self, current: &Value, root: &Value) -> bool {
+        match self {
+            Expr::Or(exprs) => exprs.iter().any(|e| e.test(current, root)),
+            Expr::And(exprs) => exprs.iter().all(|e| e.test(current, root)),
+            Expr::Not(expr) => !expr.test(current, root),
+            Expr::Exists(query) => !query.select(current, root).is_empty(),
+            Expr::Compare(left, op, right) => {
+                let left = left.value(current, root);
+                let right = right.value(current, root);
+                match op {
+                    Op::Eq => same(left, right),
+                    Op::Ne => !same(left, right),
+                    Op::Lt => less(left, right),
+                    Op::Le => less(left, right) || same(left, right),
+                    Op::Gt => less(right, left),
+                    Op::Ge => less(right, left) || same(left, right),
+                }
+            }
+        }
+    }
+}
+
+impl Operand {
+    // A query only produces a value if it matches exactly one node.
+    fn value<'a>(&'a self, current: &'a Value, root: &'a Value) -> Option<&'a Value> {
+        match self {
+            Operand::Literal(v) => Some(v),
+            Operand::Query(query) => match query.select(current, root).as_slice() {
+                [v] => Some(*v),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl Query {
+    // Selects at most one node, as required of the operands of comparisons.
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| {
+            !segment.descendant
+                && matches!(
+                    segment.selectors.as_slice(),
+                    [Selector::Name(_)] | [Selector::Index(_)]
+                )
+        })
+    }
+
+    fn select<'a>(&self, current: &'a Value, root: &'a Value) -> Vec<&'a Value> {
+        let node = if self.relative { current } else { root };
+        select(&self.segments, node, root)
+    }
+}
+
+fn same(a: Option<&Value>, b: Option<&Value>) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => equal(a, b),
+        _ => false,
+    }
+}
+
+fn less(a: Option<&Value>, b: Option<&Value>) -> bool {
+    match (a, b) {
+        (Some(Value::String(a)), Some(Value::String(b))) => a < b,
+        (Some(Value::Number(a)), Some(Value::Number(b))) => match (a, b) {
+            (Number::U64(a), Number::U64(b)) => a < b,
+            (Number::I64(a), Number::I64(b)) => a < b,
+            _ => float(a) < float(b),
+        },
+        _ => false,
+    }
+}
+
+fn float(n: &Number) -> f64 {
+    match *n {
+        Number::U64(n) => n as f64,
+        Number::I64(n) => n as f64,
+        Number::F64(n) => n,
+    }
+}
+
+// Filters, negations and parentheses are parsed and evaluated recursively, so
+// their nesting is limited.
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char, expected: &'static str) -> Result<(), JsonPathError> {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(expected))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start_matches([' ', '\t', '\n', '\r']).len();
+    }
+
+    fn error(&self, expected: &'static str) -> JsonPathError {
+        self.error_at(self.pos, expected)
+    }
+
+    fn error_at(&self, offset: usize, expected: &'static str) -> JsonPathError {
+        JsonPathError { offset, expected }
+    }
+
+    fn nested<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, JsonPathError>,
+    ) -> Result<T, JsonPathError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("at most 128 levels of nesting"));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn segments(&mut self) -> Result<Vec<Segment>, JsonPathError> {
+        let mut segments = Vec::new();
+        loop {
+            let start = self.pos;
+            self.skip_whitespace();
+            let descendant = self.eat("..");
+            let selectors = if self.peek() == Some('[') {
+                self.bracket()?
+            } else if descendant || self.eat(".") {
+                if self.eat("*") {
+                    vec![Selector::Wildcard]
+                } else {
+                    let name = self.name().ok_or_else(|| self.error("member name"))?;
+                    vec![Selector::Name(name)]
+                }
+            } else {
+                self.pos = start;
+                return Ok(segments);
+            };
+            segments.push(Segment {
+                descendant,
+                selectors,
+            });
+        }
+    }
+
+    fn name(&mut self) -> Option<String> {
+        let rest = self.rest();
+        let len = rest
+            .char_indices()
+            .find(|&(i, c)| {
+                let first = c.is_ascii_alphabetic() || c == '_' || !c.is_ascii();
+                !(first || i > 0 && c.is_ascii_digit())
+            })
+            .map_or(rest.len(), |(i, _)| i);
+        if len == 0 {
+            return None;
+        }
+        self.pos += len;
+        Some(rest[..len].to_owned())
+    }
+
+    fn bracket(&mut self) -> Result<Vec<Selector>, JsonPathError> {
+        self.expect('[', "'['")?;
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_whitespace();
+            selectors.push(self.selector()?);
+            self.skip_whitespace();
+            if !self.eat(",") {
+                self.expect(']', "']' or ','")?;
+                return Ok(selectors);
+            }
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector, JsonPathError> {
+        match self.peek() {
+            Some('\'') | Some('"') => return Ok(Selector::Name(self.string()?)),
+            Some('*') => {
+                self.pos += 1;
+                return Ok(Selector::Wildcard);
+            }
+            Some('?') => {
+                self.pos += 1;
+                return self.nested(Self::or).map(Selector::Filter);
+            }
+            _ => {}
+        }
+        let start = self.integer()?;
+        self.skip_whitespace();
+        if !self.eat(":") {
+            return start
+                .map(Selector::Index)
+                .ok_or_else(|| self.error("selector"));
+        }
+        self.skip_whitespace();
+        let end = self.integer()?;
+        self.skip_whitespace();
+        let step = if self.eat(":") {
+            self.skip_whitespace();
+            self.integer()?
+        } else {
+            None
+        };
+        Ok(Selector::Slice(start, end, step))
+    }
+
+    fn integer(&mut self) -> Result<Option<i64>, JsonPathError> {
+        let rest = self.rest();
+        let digits = rest.strip_prefix('-').unwrap_or(rest);
+        let len = digits
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(digits.len());
+        if len == 0 {
+            return Ok(None);
+        }
+        // No leading zeros, and no `-0`.
+        let negative = digits.len() < rest.len();
+        if digits.starts_with('0') && (len > 1 || negative) {
+            return Err(self.error("integer"));
+        }
+        let len = len + (rest.len() - digits.len());
+        let n = rest[..len].parse().map_err(|_| self.error("integer"))?;
+        self.pos += len;
+        Ok(Some(n))
+    }
+
+    fn string(&mut self) -> Result<String, JsonPathError> {
+        let quote = self.peek().unwrap();
+        self.pos += 1;
+        let mut string = String::new();
+        let mut chars = self.rest().chars();
+        loop {
+            let c = chars.next().ok_or_else(|| self.error("closing quote"))?;
+            let c = match c {
+                '\\' => match chars.next() {
+                    Some('b') => '\u{8}',
+                    Some('f') => '\u{c}',
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some('u') => {
+                        let hex: String = chars.by_ref().take(4).collect();
+                        u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .filter(|_| hex.len() == 4)
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| self.error("unicode escape"))?
+                    }
+                    Some(c @ '\\') | Some(c @ '/') | Some(c @ '\'') | Some(c @ '"') => c,
+                    _ => return Err(self.error("escape sequence")),
+                },
+                c if c == quote => break,
+                c => c,
+            };
+            string.push(c);
+        }
+        self.pos = self.input.len() - chars.as_str().len();
+        Ok(string)
+    }
+
+    fn or(&mut self) -> Result<Expr, JsonPathError> {
+        let mut exprs = vec![self.and()?];
+        loop {
+            self.skip_whitespace();
+            if !self.eat("||") {
+                break;
+            }
+            exprs.push(self.and()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.pop().unwrap()
+        } else {
+            Expr::Or(exprs)
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr, JsonPathError> {
+        let mut exprs = vec![self.unary()?];
+        loop {
+            self.skip_whitespace();
+            if !self.eat("&&") {
+                break;
+            }
+            exprs.push(self.unary()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.pop().unwrap()
+        } else {
+            Expr::And(exprs)
+        })
+    }
+
+    fn unary(&mut self) -> Result<Expr, JsonPathError> {
+        self.skip_whitespace();
+        if self.eat("!") {
+            return self.nested(|p| Ok(Expr::Not(Box::new(p.unary()?))));
+        }
+        if self.eat("(") {
+            return self.nested(|p| {
+                let expr = p.or()?;
+                p.skip_whitespace();
+                p.expect(')', "')'")?;
+                Ok(expr)
+            });
+        }
+        let start = self.pos;
+        let left = self.operand()?;
+        self.skip_whitespace();
+        let op = [
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+        ]
+        .iter()
+        .find(|(s, _)| self.eat(s))
+        .map(|(_, op)| *op);
+        match (left, op) {
+            (left, Some(op)) => {
+                let left = self.comparable(left, start)?;
+                self.skip_whitespace();
+                let start = self.pos;
+                let right = self.operand()?;
+                let right = self.comparable(right, start)?;
+                Ok(Expr::Compare(left, op, right))
+            }
+            (Operand::Query(query), None) => Ok(Expr::Exists(query)),
+            (Operand::Literal(_), None) => Err(self.error("comparison operator")),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Operand, JsonPathError> {
+        let relative = match self.peek() {
+            Some('@') => true,
+            Some('$') => false,
+            Some('\'') | Some('"') => return Ok(Operand::Literal(Value::String(self.string()?))),
+            _ => return self.literal().map(Operand::Literal),
+        };
+        self.pos += 1;
+        Ok(Operand::Query(Query {
+            relative,
+            segments: self.segments()?,
+        }))
+    }
+
+    // Check that `operand`, which started at `start`, may be compared.
+    fn comparable(&self, operand: Operand, start: usize) -> Result<Operand, JsonPathError> {
+        match operand {
+            Operand::Query(query) if !query.is_singular() => {
+                Err(self.error_at(start, "literal or singular query"))
+            }
+            operand => Ok(operand),
+        }
+    }
+
+    fn literal(&mut self) -> Result<Value, JsonPathError> {
+        for (s, v) in [
+            ("true", Value::Bool(true)),
+            ("false", Value::Bool(false)),
+            ("null", Value::Null),
+        ] {
+            if self.eat(s) {
+                return Ok(v);
+            }
+        }
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'))
+            .unwrap_or(rest.len());
+        let number = &rest[..len];
+        if number_len(number) != Some(len) {
+            return Err(self.error("literal or query"));
+        }
+        let number = if number.contains(['.', 'e', 'E']) {
+            number.parse().ok().map(Number::F64)
+        } else if number.starts_with('-') {
+            number.parse().ok().map(Number::I64)
+        } else {
+            number.parse().ok().map(Number::U64)
+        };
+        let number = number.ok_or_else(|| self.error("literal or query"))?;
+        self.pos += len;
+        Ok(Value::Number(number))
+    }
+}
+
+// Length of the number at the start of `s`, following the RFC grammar: no
+// leading `+` or zeros, and digits on both sides of the decimal point.
+fn number_len(s: &str) -> Option<usize> {
+    let digits = |from: usize| {
+        s[from..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(s.len() - from)
+    };
+    let mut len = s.starts_with('-') as usize;
+    len += match digits(len) {
+        0 => return None,
+        n if n > 1 && s[len..].starts_with('0') => return None,
+        n => n,
+    };
+    if s[len..].starts_with('.') {
+        len += 1;
+        match digits(len) {
+            0 => return None,
+            n => len += n,
+        }
+    }
+    if s[len..].starts_with(['e', 'E']) {
+        len += 1;
+        if s[len..].starts_with(['-', '+']) {
+            len += 1;
+        }
+        match digits(len) {
+            0 => return None,
+            n => len += n,
+        }
+    }
+    Some(len)
+}
+
+#[test]
+fn bookstore() {
+    use miniserde::json;
+
+    // Example document from RFC 9535, section 1.5.
+    let v: Value = json::from_str(
+        r#"{"store": {
+            "book": [
+                {"category": "reference", "author": "Nigel Rees",
+                 "title": "Sayings of the Century", "price": 8.95},
+                {"category": "fiction", "author": "Evelyn Waugh",
+                 "title": "Sword of Honour", "price": 12.99},
+                {"category": "fiction", "author": "Herman Melville",
+                 "title": "Moby Dick", "isbn": "0-553-21311-3", "price": 8.99},
+                {"category": "fiction", "author": "J. R. R. Tolkien",
+                 "title": "The Lord of the Rings", "isbn": "0-395-19395-8", "price": 22.99}
+            ],
+            "bicycle": {"color": "red", "price": 399}
+        }}"#,
+    )
+    .unwrap();
+    let cases = [
+        (
+            "$.store.book[*].author",
+            r#"["Nigel Rees","Evelyn Waugh","Herman Melville","J. R. R. Tolkien"]"#,
+        ),
+        (
+            "$..author",
+            r#"["Nigel Rees","Evelyn Waugh","Herman Melville","J. R. R. Tolkien"]"#,
+        ),
+        ("$.store..price", r#"[399,8.95,12.99,8.99,22.99]"#),
+        ("$..book[2].author", r#"["Herman Melville"]"#),
+        ("$..book[-1].title", r#"["The Lord of the Rings"]"#),
+        ("$..book[0,1].price", r#"[8.95,12.99]"#),
+        ("$..book[:2].price", r#"[8.95,12.99]"#),
+        ("$..book[::-2].price", r#"[22.99,12.99]"#),
+        ("$..book[1:-1:1].price", r#"[12.99,8.99]"#),
+        ("$..book[1::9223372036854775807].price", r#"[12.99]"#),
+        ("$..book[::-9223372036854775808].price", r#"[22.99]"#),
+        (
+            "$..book[-9223372036854775808:9223372036854775807:2].price",
+            r#"[8.95,8.99]"#,
+        ),
+        ("$..book[?@.isbn].price", r#"[8.99,22.99]"#),
+        ("$..book[?(@.price < 10)].title", r#"["Sayings of the Century","Moby Dick"]"#),
+        (
+            "$..book[?@.price > $.store.bicycle.price || !@.isbn && @.category != 'reference'].price",
+            r#"[12.99]"#,
+        ),
+        (
+            r#"$.store.book[?(@.author >= "J" && (@.price <= 9 || @["price"] == 22.99))].author"#,
+            r#"["Nigel Rees","J. R. R. Tolkien"]"#,
+        ),
+        ("$.store['bicycle', \"x\"].color", r#"["red"]"#),
+        ("$..*[?@ == 'red']", r#"["red"]"#),
+        ("$.store.book[?@.missing == $.nothing].price", r#"[8.95,12.99,8.99,22.99]"#),
+        ("$.store.bicycle[0]", "[]"),
+        ("$.store.book[9]", "[]"),
+        ("$.store.book[3:1]", "[]"),
+        ("$.store.book[::0]", "[]"),
+    ];
+    for (path, expected) in &cases {
+        let matches: Vec<String> = query(&v, path)
+            .unwrap()
+            .into_iter()
+            .map(json::to_string)
+            .collect();
+        assert_eq!(format!("[{}]", matches.join(",")), *expected, "{}", path);
+    }
+    assert_eq!(query(&v, "$..*").unwrap().len(), 27);
+}
+
+#[test]
+fn typed() {
+    use crate::error::Callback;
+    use miniserde::json;
+
+    let v: Value =
+        json::from_str(r#"{"items": [{"price": 5}, {"price": 12}, {"price": "3"}]}"#).unwrap();
+    let prices: Vec<u32> = from_value_query(&v, "$.items[0:2].price").unwrap();
+    assert_eq!(prices, [5, 12]);
+
+    match from_value_query::<u32>(&v, "$.items[*].price") {
+        Err(FromValueQueryError::Deserialize(2, err)) => {
+            assert_eq!(err.callback(), Callback::String)
+        }
+        other => panic!("{:?}", other),
+    }
+
+    for (path, offset) in [
+        ("items", 0),
+        ("$.items[", 8),
+        ("$.items[?@.price <]", 18),
+        ("$.items[?1]", 10),
+        ("$.items.'x'", 8),
+        ("$.items x", 7),
+        ("$.items[-0]", 8),
+        ("$.items[01]", 8),
+        ("$.items[0:-0]", 10),
+        ("$.items[?@.price == 01]", 20),
+        ("$.items[?@.price == -01]", 20),
+        ("$.items[?@.price > +1]", 19),
+        ("$.items[?@.price == 1.]", 20),
+        ("$.items[?@.price == .5]", 20),
+        ("$.items[?@.price == 1e]", 20),
+        ("$.items[?@..price == 1]", 9),
+        ("$.items[?@[0,1] == 1]", 9),
+        ("$.items[?@.price == $.items[*].price]", 20),
+    ] {
+        match from_value_query::<Value>(&v, path) {
+            Err(FromValueQueryError::Syntax(err)) => assert_eq!(err.offset(), offset, "{}", path),
+            other => panic!("{}: {:?}", path, other),
+        }
+    }
+
+    let prices: Vec<u32> = from_value_query(&v, "$.items[?@.price > -0.0e+0].price").unwrap();
+    assert_eq!(prices, [5, 12]);
+    let prices: Vec<u32> =
+        from_value_query(&v, "$.items[?$.items[0].price == @.price].price").unwrap();
+    assert_eq!(prices, [5]);
+
+    let deep = |open: &str, close: &str, n| {
+        format!("$.items[?{}@.price{}]", open.repeat(n), close.repeat(n))
+    };
+    for (open, close) in [("!", ""), ("(", ")"), ("@[?", "]")] {
+        assert!(query(&v, &deep(open, close, 100)).is_ok(), "{}", open);
+        let err = query(&v, &deep(open, close, 200_000)).unwrap_err();
+        assert_eq!(err.offset(), 9 + 128 * open.len(), "{}", open);
+    }
+}
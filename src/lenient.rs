@@ -0,0 +1,159 @@
+use crate::de::from_value_detailed;
+use crate::error::DetailedError;
+use miniserde::{
+    de::{Seq, Visitor},
+    json::Value,
+    make_place, Deserialize, Result,
+};
+use std::mem;
+
+make_place!(Place);
+
+/// A JSON array whose elements are deserialized one by one, keeping those
+/// that match `T` and setting the others aside.
+///
+/// Each element is first captured as a `Value` and then converted with
+/// `from_value_detailed`, so a bad element cannot fail the whole array. This
+/// works with any driver, including `miniserde::json::from_str`.
+///
+/// The conversion does not know about the `FromValueOptions` of the
+/// enclosing call and always uses the defaults: inside a `Lenient`, unknown
+/// keys are neither denied nor reported and scalars are not coerced. Limits
+/// still apply, as they are checked while the element is captured.
+#[derive(Clone, Debug)]
+pub struct Lenient<T> {
+    /// Elements that deserialized successfully, in order.
+    pub items: Vec<T>,
+    /// Index and error of every element that did not. Error paths are
+    /// relative to the element.
+    pub skipped: Vec<(usize, DetailedError)>,
+}
+
+impl<T> Default for Lenient<T> {
+    fn default() -> Self {
+        Lenient {
+            items: Vec::new(),
+            skipped: Vec::new(),
+        }
+    }
+}
+
+impl<T: Deserialize> Deserialize for Lenient<T> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        Place::new(out)
+    }
+}
+
+impl<T: Deserialize> Visitor for Place<Lenient<T>> {
+    fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
+        Ok(Box::new(LenientBuilder {
+            out: &mut self.out,
+            lenient: Default::default(),
+            index: 0,
+            element: None,
+        }))
+    }
+}
+
+struct LenientBuilder<'a, T> {
+    out: &'a mut Option<Lenient<T>>,
+    lenient: Lenient<T>,
+    index: usize,
+    element: Option<Value>,
+}
+
+impl<'a, T: Deserialize> LenientBuilder<'a, T> {
+    fn shift(&mut self) {
+        if let Some(e) = self.element.take() {
+            match from_value_detailed(&e) {
+                Ok(item) => self.lenient.items.push(item),
+                Err(err) => self.lenient.skipped.push((self.index, err)),
+            }
+            self.index += 1;
+        }
+    }
+}
+
+impl<'a, T: Deserialize> Seq for LenientBuilder<'a, T> {
+    fn element(&mut self) -> Result<&mut dyn Visitor> {
+        self.shift();
+        Ok(Deserialize::begin(&mut self.element))
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.shift();
+        *self.out = Some(mem::take(&mut self.lenient));
+        Ok(())
+    }
+}
+
+#[test]
+#[allow(non_local_definitions)]
+fn lenient() {
+    use crate::error::Callback;
+    use miniserde::json;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Item {
+        id: u32,
+    }
+    #[derive(Deserialize, Debug)]
+    struct Feed {
+        items: Lenient<Item>,
+    }
+
+    let input = r#"{"items": [{"id": 1}, {"id": "2"}, 3, {"id": 4, "x": [null]}, {}]}"#;
+    let feeds = [
+        json::from_str::<Feed>(input).unwrap(),
+        crate::from_value(&json::from_str(input).unwrap()).unwrap(),
+    ];
+    for feed in &feeds {
+        assert_eq!(feed.items.items, [Item { id: 1 }, Item { id: 4 }]);
+        let skipped: Vec<_> = feed
+            .items
+            .skipped
+            .iter()
+            .map(|(i, err)| (*i, err.path(), err.callback()))
+            .collect();
+        assert_eq!(
+            skipped,
+            [
+                (1, "/id", Callback::String),
+                (2, "", Callback::Nonnegative),
+                (4, "", Callback::MapFinish),
+            ]
+        );
+    }
+}
+
+#[test]
+#[allow(non_local_definitions)]
+fn default_options() {
+    use crate::de::FromValueOptions;
+    use crate::error::{ErrorKind, Limit};
+    use miniserde::json;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Item {
+        id: u32,
+    }
+    #[derive(Deserialize, Debug)]
+    struct Feed {
+        items: Lenient<Item>,
+    }
+
+    let v: Value = json::from_str(r#"{"items": [{"id": 1, "x": 0}, {"id": "2"}]}"#).unwrap();
+    let options = FromValueOptions::new().deny_unknown_keys(true).coerce(true);
+    let (feed, ignored) = options.from_value_report::<Feed>(&v).unwrap();
+    assert_eq!(feed.items.items, [Item { id: 1 }]);
+    assert_eq!(feed.items.skipped.len(), 1);
+    assert_eq!(feed.items.skipped[0].0, 1);
+    assert!(ignored.is_empty());
+
+    let err = FromValueOptions::new()
+        .max_depth(2)
+        .from_value::<Feed>(&v)
+        .unwrap_err();
+    assert_eq!(err.path(), "/items/0");
+    assert_eq!(err.kind(), &ErrorKind::LimitExceeded(Limit::Depth(2)));
+}
@@ -0,0 +1,248 @@
+use crate::eq::equal;
+use crate::owned::from_value_owned;
+use crate::ser::to_value;
+use miniserde::{
+    json::{Object, Value},
+    Deserialize, Result,
+};
+use std::collections::btree_map;
+
+/// Deserialize `v` layered over `defaults`.
+///
+/// Objects are merged key by key, recursively; any other value in `v`,
+/// including `null`, replaces the default. This fills in fields that `v`
+/// leaves out, which miniserde would otherwise reject as missing.
+pub fn from_value_with_defaults<T: Deserialize>(v: &Value, defaults: &Value) -> Result<T> {
+    let mut merged = to_value(defaults);
+    merge(&mut merged, v, false);
+    from_value_owned(merged)
+}
+
+/// Apply an RFC 7396 JSON Merge Patch to `target`.
+///
+/// Objects in `patch` are merged recursively, `null` members delete the
+/// corresponding key and any other value replaces the target. Nesting depth
+/// is not limited by the call stack.
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    merge(target, patch, true);
+}
+
+/// Compute a merge patch that turns `source` into `target` when applied with
+/// `merge_patch`.
+///
+/// Merge patches cannot express setting an object member to `null`; such
+/// members of `target` are copied into the patch and end up removed.
+pub fn merge_patch_diff(source: &Value, target: &Value) -> Value {
+    let (source, target) = match (source, target) {
+        (Value::Object(source), Value::Object(target)) => (source, target),
+        _ => return to_value(target),
+    };
+    let mut stack = vec![Frame::new(None, source, target)];
+    loop {
+        let frame = stack.last_mut().unwrap();
+        let source = frame.source;
+        match frame.entries.next() {
+            Some((k, t)) => match (source.get(k), t) {
+                (Some(Value::Object(s)), Value::Object(t)) => {
+                    stack.push(Frame::new(Some(k), s, t));
+                }
+                (Some(s), t) if equal(s, t) => {}
+                _ => {
+                    frame.patch.insert(k.clone(), to_value(t));
+                }
+            },
+            None => {
+                let frame = stack.pop().unwrap();
+                match (stack.last_mut(), frame.key) {
+                    (Some(parent), Some(key)) => {
+                        if !frame.patch.is_empty() {
+                            parent.patch.insert(key.clone(), Value::Object(frame.patch));
+                        }
+                    }
+                    _ => return Value::Object(frame.patch),
+                }
+            }
+        }
+    }
+}
+
+// An object being diffed by `merge_patch_diff`, along with the patch built
+// for it so far.
+struct Frame<'a> {
+    key: Option<&'a String>,
+    source: &'a Object,
+    entries: btree_map::Iter<'a, String, Value>,
+    patch: Object,
+}
+
+impl<'a> Frame<'a> {
+    fn new(key: Option<&'a String>, source: &'a Object, target: &'a Object) -> Self {
+        let patch = source
+            .keys()
+            .filter(|k| !target.contains_key(*k))
+            .map(|k| (k.clone(), Value::Null))
+            .collect();
+        Frame {
+            key,
+            source,
+            entries: target.iter(),
+            patch,
+        }
+    }
+}
+
+// Deep-merge `overlay` into `target`, without recursion. When `patch` is set,
+// `null` members of `overlay` delete keys instead of being copied over.
+fn merge(target: &mut Value, overlay: &Value, patch: bool) {
+    let mut stack = vec![(target, overlay)];
+    while let Some((target, overlay)) = stack.pop() {
+        let overlay = match overlay {
+            Value::Object(overlay) => overlay,
+            _ => {
+                *target = to_value(overlay);
+                continue;
+            }
+        };
+        if !matches!(target, Value::Object(_)) {
+            *target = Value::Object(Object::new());
+        }
+        let target = match target {
+            Value::Object(target) => target,
+            _ => unreachable!(),
+        };
+        for (k, v) in overlay.iter() {
+            match v {
+                Value::Null if patch => {
+                    target.remove(k);
+                }
+                Value::Object(_) => {
+                    target.entry(k.clone()).or_insert(Value::Null);
+                }
+                _ => {
+                    target.insert(k.clone(), to_value(v));
+                }
+            }
+        }
+        for (k, target) in target {
+            if let Some(overlay @ Value::Object(_)) = overlay.get(k) {
+                stack.push((target, overlay));
+            }
+        }
+    }
+}
+
+#[test]
+#[allow(non_local_definitions)]
+fn defaults() {
+    use miniserde::json;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+        tags: Vec<String>,
+    }
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Config {
+        server: Server,
+        debug: bool,
+        name: Option<String>,
+    }
+
+    let defaults: Value = json::from_str(
+        r#"{"server": {"host": "localhost", "port": 80, "tags": ["a", "b"]}, "debug": false, "name": "x"}"#,
+    )
+    .unwrap();
+    let v: Value =
+        json::from_str(r#"{"server": {"port": 8080, "tags": ["c"]}, "name": null}"#).unwrap();
+    assert!(crate::from_value::<Config>(&v).is_err());
+    assert_eq!(
+        from_value_with_defaults::<Config>(&v, &defaults).unwrap(),
+        Config {
+            server: Server {
+                host: "localhost".into(),
+                port: 8080,
+                tags: vec!["c".into()],
+            },
+            debug: false,
+            name: None,
+        }
+    );
+
+    let v: Value = json::from_str(r#"{"server": 1}"#).unwrap();
+    assert!(from_value_with_defaults::<Config>(&v, &defaults).is_err());
+}
+
+#[test]
+fn rfc7396() {
+    use miniserde::json;
+
+    // Examples from Appendix A of RFC 7396.
+    let cases = [
+        (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+        (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+        (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+        (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+        (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+        (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+        (
+            r#"{"a":{"b":"c"}}"#,
+            r#"{"a":{"b":"d","c":null}}"#,
+            r#"{"a":{"b":"d"}}"#,
+        ),
+        (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+        (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+        (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+        (r#"{"a":"foo"}"#, r#"null"#, r#"null"#),
+        (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+        (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"a":1,"e":null}"#),
+        (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+        (
+            r#"{}"#,
+            r#"{"a":{"bb":{"ccc":null}}}"#,
+            r#"{"a":{"bb":{}}}"#,
+        ),
+    ];
+    for (original, patch, result) in &cases {
+        let mut target: Value = json::from_str(original).unwrap();
+        let patch: Value = json::from_str(patch).unwrap();
+        let result: Value = json::from_str(result).unwrap();
+        merge_patch(&mut target, &patch);
+        assert!(equal(&target, &result), "{}", json::to_string(&target));
+    }
+}
+
+#[test]
+fn diff() {
+    use miniserde::json;
+
+    let source: Value =
+        json::from_str(r#"{"a": {"b": 1, "c": [1], "d": {"e": 1}}, "f": 1, "g": 2}"#).unwrap();
+    let target: Value =
+        json::from_str(r#"{"a": {"b": 2, "c": [1], "d": {"e": 1}}, "g": 2, "h": {"i": 1}}"#)
+            .unwrap();
+    let patch = merge_patch_diff(&source, &target);
+    let expected: Value = json::from_str(r#"{"a": {"b": 2}, "f": null, "h": {"i": 1}}"#).unwrap();
+    assert!(equal(&patch, &expected), "{}", json::to_string(&patch));
+
+    let mut patched = source.clone();
+    merge_patch(&mut patched, &patch);
+    assert!(equal(&patched, &target));
+
+    let patch = merge_patch_diff(&source, &source);
+    assert!(equal(&patch, &Value::Object(Object::new())));
+}
+
+#[test]
+fn deep_patch() {
+    let mut patch = Value::Bool(true);
+    for _ in 0..100_000 {
+        let mut object = Object::new();
+        object.insert("a".to_owned(), patch);
+        patch = Value::Object(object);
+    }
+    let mut target = Value::Null;
+    merge_patch(&mut target, &patch);
+    assert!(equal(&target, &patch));
+    assert!(equal(&merge_patch_diff(&Value::Null, &patch), &patch));
+}
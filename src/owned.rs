@@ -0,0 +1,88 @@
+use miniserde::{
+    de::{Map, Seq, Visitor},
+    json::{Number, Value},
+    Deserialize, Error, Result,
+};
+use std::collections::btree_map;
+use std::vec;
+
+enum Event<'a> {
+    Visitor(Value, &'a mut dyn Visitor),
+    Seq(vec::IntoIter<Value>, Box<dyn Seq>),
+    Map(btree_map::IntoIter<String, Value>, Box<dyn Map>),
+}
+
+/// Same as `from_value`, but consumes the `Value`.
+///
+/// Each array element and object entry is dropped as soon as its visitor is
+/// done with it, so the contents of the input are released while the output
+/// is being built rather than after.
+pub fn from_value_owned<T: Deserialize>(v: Value) -> Result<T> {
+    let mut out = None;
+    let mut stack = Vec::new();
+    stack.push(Event::Visitor(v, T::begin(&mut out)));
+    while let Some(event) = stack.pop() {
+        match event {
+            Event::Visitor(v, visitor) => match v {
+                Value::Null => visitor.null()?,
+                Value::Bool(b) => visitor.boolean(b)?,
+                Value::String(s) => visitor.string(&s)?,
+                Value::Number(Number::U64(n)) => visitor.nonnegative(n)?,
+                Value::Number(Number::I64(n)) => visitor.negative(n)?,
+                Value::Number(Number::F64(n)) => visitor.float(n)?,
+                Value::Array(a) => {
+                    stack.push(Event::Seq(
+                        a.into_iter(),
+                        careful!(visitor.seq()? as Box<dyn Seq>),
+                    ));
+                }
+                Value::Object(o) => {
+                    stack.push(Event::Map(
+                        o.into_iter(),
+                        careful!(visitor.map()? as Box<dyn Map>),
+                    ));
+                }
+            },
+            Event::Seq(mut arr, mut seq) => match arr.next() {
+                Some(v) => {
+                    let element = careful!(seq.element()? as &mut dyn Visitor);
+                    stack.push(Event::Seq(arr, seq));
+                    stack.push(Event::Visitor(v, element));
+                }
+                None => seq.finish()?,
+            },
+            Event::Map(mut obj, mut map) => match obj.next() {
+                Some((k, v)) => {
+                    let key = careful!(map.key(&k)? as &mut dyn Visitor);
+                    stack.push(Event::Map(obj, map));
+                    stack.push(Event::Visitor(v, key));
+                }
+                None => map.finish()?,
+            },
+        }
+    }
+    out.ok_or(Error)
+}
+
+#[test]
+#[allow(non_local_definitions)]
+fn owned() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct S {
+        s: String,
+        v: Vec<Option<u8>>,
+    }
+    let v: Value =
+        miniserde::json::from_str(r#"{"s": "test", "v": [1, null, 3], "x": [{}]}"#).unwrap();
+    let s: S = from_value_owned(v).unwrap();
+    assert_eq!(
+        S {
+            s: "test".into(),
+            v: vec![Some(1), None, Some(3)]
+        },
+        s
+    );
+
+    let v: Value = miniserde::json::from_str(r#"{"s": "test", "v": [1, "x"]}"#).unwrap();
+    assert!(from_value_owned::<S>(v).is_err());
+}
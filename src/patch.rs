@@ -0,0 +1,494 @@
+use crate::de::from_value_detailed;
+use crate::eq::equal;
+use crate::error::DetailedError;
+use crate::pointer::{self, insert, remove, resolve, resolve_mut, PointerError};
+use crate::ser::to_value;
+use miniserde::{
+    de::{Map, Visitor},
+    json::Value,
+    make_place,
+    ser::{self, Fragment},
+    Deserialize, Error, Serialize,
+};
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+
+make_place!(Place);
+
+/// A single RFC 6902 JSON Patch operation.
+///
+/// Deserializes from and serializes to the JSON representation, such as
+/// `{"op": "add", "path": "/a", "value": 1}`. Members other than `op`,
+/// `path`, `from` and `value` are ignored.
+#[derive(Clone, Debug)]
+pub enum Operation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+/// Error applying a JSON Patch. Indices refer to the failing operation.
+#[derive(Clone, Debug)]
+pub enum PatchError {
+    /// The patch document is not an array of valid operations.
+    Invalid(DetailedError),
+    /// A `path` or `from` pointer is malformed or designates a missing value.
+    Pointer { index: usize, error: PointerError },
+    /// A `test` operation found a different value.
+    TestFailed { index: usize },
+    /// A `move` operation tried to move a value into one of its children.
+    MoveIntoChild { index: usize },
+}
+
+impl Display for PatchError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatchError::Invalid(err) => write!(formatter, "invalid JSON Patch: {}", err),
+            PatchError::Pointer { index, error } => {
+                write!(formatter, "operation {}: {}", index, error)
+            }
+            PatchError::TestFailed { index } => {
+                write!(formatter, "operation {}: test failed", index)
+            }
+            PatchError::MoveIntoChild { index } => write!(
+                formatter,
+                "operation {}: cannot move a value into one of its children",
+                index,
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// Apply an RFC 6902 JSON Patch document to `target`.
+///
+/// The patch is deserialized into a list of `Operation`s and applied with
+/// `json_patch_ops`.
+pub fn json_patch(target: &mut Value, patch: &Value) -> Result<(), PatchError> {
+    let ops: Vec<Operation> = from_value_detailed(patch).map_err(PatchError::Invalid)?;
+    json_patch_ops(target, &ops)
+}
+
+/// Apply JSON Patch operations to `target`, all or nothing.
+///
+/// The operations are applied to a copy of `target`, which only replaces it
+/// once every operation has succeeded. Removing the root sets it to `null`.
+pub fn json_patch_ops(target: &mut Value, ops: &[Operation]) -> Result<(), PatchError> {
+    let mut doc = to_value(target);
+    for (index, op) in ops.iter().enumerate() {
+        apply(&mut doc, op, index)?;
+    }
+    *target = doc;
+    Ok(())
+}
+
+/// Compute JSON Patch operations that turn `source` into `target`.
+///
+/// Objects and arrays are compared member by member, without recursion.
+/// Array elements are matched by index: extra elements are added at the end
+/// or removed from the end, so insertions in the middle of an array produce
+/// a replacement of every following element.
+pub fn json_patch_diff(source: &Value, target: &Value) -> Vec<Operation> {
+    let mut ops = Vec::new();
+    let mut stack = vec![(String::new(), source, target)];
+    while let Some((path, source, target)) = stack.pop() {
+        match (source, target) {
+            (Value::Object(s), Value::Object(t)) => {
+                for k in s.keys().filter(|k| !t.contains_key(*k)) {
+                    ops.push(Operation::Remove {
+                        path: child(&path, k),
+                    });
+                }
+                for (k, t) in t.iter().rev() {
+                    match s.get(k) {
+                        Some(s) => stack.push((child(&path, k), s, t)),
+                        None => ops.push(Operation::Add {
+                            path: child(&path, k),
+                            value: to_value(t),
+                        }),
+                    }
+                }
+            }
+            (Value::Array(s), Value::Array(t)) => {
+                for i in (t.len()..s.len()).rev() {
+                    ops.push(Operation::Remove {
+                        path: format!("{}/{}", path, i),
+                    });
+                }
+                for t in t.iter().skip(s.len()) {
+                    ops.push(Operation::Add {
+                        path: format!("{}/-", path),
+                        value: to_value(t),
+                    });
+                }
+                for (i, (s, t)) in s.iter().zip(t.iter()).enumerate().rev() {
+                    stack.push((format!("{}/{}", path, i), s, t));
+                }
+            }
+            (s, t) if equal(s, t) => {}
+            (_, t) => ops.push(Operation::Replace {
+                path,
+                value: to_value(t),
+            }),
+        }
+    }
+    ops
+}
+
+fn child(path: &str, key: &str) -> String {
+    format!("{}/{}", path, pointer::escape(key))
+}
+
+fn apply(doc: &mut Value, op: &Operation, index: usize) -> Result<(), PatchError> {
+    let error = |error| PatchError::Pointer { index, error };
+    match op {
+        Operation::Add { path, value } => insert(doc, path, to_value(value), false)
+            .map(drop)
+            .map_err(error),
+        Operation::Remove { path } => remove(doc, path).map(drop).map_err(error),
+        Operation::Replace { path, value } => {
+            *resolve_mut(doc, path).map_err(error)? = to_value(value);
+            Ok(())
+        }
+        Operation::Move { from, path } => {
+            if path == from {
+                return resolve(doc, from).map(drop).map_err(error);
+            }
+            if path.starts_with(from.as_str()) && path[from.len()..].starts_with('/') {
+                return Err(PatchError::MoveIntoChild { index });
+            }
+            let value = remove(doc, from).map_err(error)?;
+            insert(doc, path, value, false).map(drop).map_err(error)
+        }
+        Operation::Copy { from, path } => {
+            let value = to_value(resolve(doc, from).map_err(error)?);
+            insert(doc, path, value, false).map(drop).map_err(error)
+        }
+        Operation::Test { path, value } => {
+            if equal(resolve(doc, path).map_err(error)?, value) {
+                Ok(())
+            } else {
+                Err(PatchError::TestFailed { index })
+            }
+        }
+    }
+}
+
+impl Operation {
+    fn name(&self) -> &'static str {
+        match self {
+            Operation::Add { .. } => "add",
+            Operation::Remove { .. } => "remove",
+            Operation::Replace { .. } => "replace",
+            Operation::Move { .. } => "move",
+            Operation::Copy { .. } => "copy",
+            Operation::Test { .. } => "test",
+        }
+    }
+}
+
+impl Deserialize for Operation {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        Place::new(out)
+    }
+}
+
+impl Visitor for Place<Operation> {
+    fn map(&mut self) -> miniserde::Result<Box<dyn Map + '_>> {
+        Ok(Box::new(OperationBuilder {
+            out: &mut self.out,
+            op: None,
+            path: None,
+            from: None,
+            value: None,
+        }))
+    }
+}
+
+// The `op` member, rejected as soon as it is read if it names no operation.
+enum Op {
+    Add,
+    Remove,
+    Replace,
+    Move,
+    Copy,
+    Test,
+}
+
+impl Deserialize for Op {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        Place::new(out)
+    }
+}
+
+impl Visitor for Place<Op> {
+    fn string(&mut self, s: &str) -> miniserde::Result<()> {
+        self.out = Some(match s {
+            "add" => Op::Add,
+            "remove" => Op::Remove,
+            "replace" => Op::Replace,
+            "move" => Op::Move,
+            "copy" => Op::Copy,
+            "test" => Op::Test,
+            _ => return Err(Error),
+        });
+        Ok(())
+    }
+}
+
+struct OperationBuilder<'a> {
+    out: &'a mut Option<Operation>,
+    op: Option<Op>,
+    path: Option<String>,
+    from: Option<String>,
+    // `Value` deserializes `null` as `Some(Value::Null)`, so an explicit null
+    // is told apart from a missing member.
+    value: Option<Value>,
+}
+
+impl<'a> Map for OperationBuilder<'a> {
+    fn key(&mut self, k: &str) -> miniserde::Result<&mut dyn Visitor> {
+        Ok(match k {
+            "op" => Deserialize::begin(&mut self.op),
+            "path" => Deserialize::begin(&mut self.path),
+            "from" => Deserialize::begin(&mut self.from),
+            "value" => Deserialize::begin(&mut self.value),
+            _ => <dyn Visitor>::ignore(),
+        })
+    }
+
+    fn finish(&mut self) -> miniserde::Result<()> {
+        let path = self.path.take().ok_or(Error)?;
+        let from = self.from.take().ok_or(Error);
+        let value = self.value.take().ok_or(Error);
+        let op = match self.op.take().ok_or(Error)? {
+            Op::Add => Operation::Add {
+                path,
+                value: value?,
+            },
+            Op::Remove => Operation::Remove { path },
+            Op::Replace => Operation::Replace {
+                path,
+                value: value?,
+            },
+            Op::Move => Operation::Move { from: from?, path },
+            Op::Copy => Operation::Copy { from: from?, path },
+            Op::Test => Operation::Test {
+                path,
+                value: value?,
+            },
+        };
+        *self.out = Some(op);
+        Ok(())
+    }
+}
+
+impl Serialize for Operation {
+    fn begin(&self) -> Fragment<'_> {
+        Fragment::Map(Box::new(OperationStream {
+            op: self,
+            name: self.name(),
+            state: 0,
+        }))
+    }
+}
+
+struct OperationStream<'a> {
+    op: &'a Operation,
+    name: &'static str,
+    state: usize,
+}
+
+impl<'a> ser::Map for OperationStream<'a> {
+    fn next(&mut self) -> Option<(Cow<'_, str>, &dyn Serialize)> {
+        let (from, path, value) = match self.op {
+            Operation::Add { path, value }
+            | Operation::Replace { path, value }
+            | Operation::Test { path, value } => (None, path, Some(value)),
+            Operation::Remove { path } => (None, path, None),
+            Operation::Move { from, path } | Operation::Copy { from, path } => {
+                (Some(from), path, None)
+            }
+        };
+        loop {
+            self.state += 1;
+            let entry: (&str, &dyn Serialize) = match self.state {
+                1 => ("op", &self.name),
+                2 => match from {
+                    Some(from) => ("from", from),
+                    None => continue,
+                },
+                3 => ("path", path),
+                4 => match value {
+                    Some(value) => ("value", value),
+                    None => continue,
+                },
+                _ => return None,
+            };
+            return Some((Cow::Borrowed(entry.0), entry.1));
+        }
+    }
+}
+
+#[test]
+fn rfc6902() {
+    use miniserde::json;
+
+    // Examples from Appendix A of RFC 6902.
+    let cases = [
+        (
+            r#"{"foo": "bar"}"#,
+            r#"[{"op": "add", "path": "/baz", "value": "qux"}]"#,
+            r#"{"baz": "qux", "foo": "bar"}"#,
+        ),
+        (
+            r#"{"foo": ["bar", "baz"]}"#,
+            r#"[{"op": "add", "path": "/foo/1", "value": "qux"}]"#,
+            r#"{"foo": ["bar", "qux", "baz"]}"#,
+        ),
+        (
+            r#"{"baz": "qux", "foo": "bar"}"#,
+            r#"[{"op": "remove", "path": "/baz"}]"#,
+            r#"{"foo": "bar"}"#,
+        ),
+        (
+            r#"{"foo": ["bar", "qux", "baz"]}"#,
+            r#"[{"op": "remove", "path": "/foo/1"}]"#,
+            r#"{"foo": ["bar", "baz"]}"#,
+        ),
+        (
+            r#"{"baz": "qux", "foo": "bar"}"#,
+            r#"[{"op": "replace", "path": "/baz", "value": "boo"}]"#,
+            r#"{"baz": "boo", "foo": "bar"}"#,
+        ),
+        (
+            r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+            r#"[{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]"#,
+            r#"{"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}"#,
+        ),
+        (
+            r#"{"foo": ["all", "grass", "cows", "eat"]}"#,
+            r#"[{"op": "move", "from": "/foo/1", "path": "/foo/3"}]"#,
+            r#"{"foo": ["all", "cows", "eat", "grass"]}"#,
+        ),
+        (
+            r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#,
+            r#"[{"op": "test", "path": "/baz", "value": "qux"},
+                {"op": "test", "path": "/foo/1", "value": 2}]"#,
+            r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#,
+        ),
+        (
+            r#"{"foo": "bar"}"#,
+            r#"[{"op": "add", "path": "/child", "value": {"grandchild": {}}}]"#,
+            r#"{"foo": "bar", "child": {"grandchild": {}}}"#,
+        ),
+        (
+            r#"{"foo": "bar"}"#,
+            r#"[{"op": "add", "path": "/baz", "value": "qux", "xyz": 123}]"#,
+            r#"{"foo": "bar", "baz": "qux"}"#,
+        ),
+        (
+            r#"{"foo": ["bar"]}"#,
+            r#"[{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]"#,
+            r#"{"foo": ["bar", ["abc", "def"]]}"#,
+        ),
+        (
+            r#"{"/": 9, "~1": 10}"#,
+            r#"[{"op": "test", "path": "/~01", "value": 10}]"#,
+            r#"{"/": 9, "~1": 10}"#,
+        ),
+        (
+            r#"{"foo": null}"#,
+            r#"[{"op": "test", "path": "/foo", "value": null},
+                {"op": "copy", "from": "/foo", "path": "/bar"}]"#,
+            r#"{"foo": null, "bar": null}"#,
+        ),
+    ];
+    for (doc, patch, result) in &cases {
+        let mut target: Value = json::from_str(doc).unwrap();
+        let patch: Value = json::from_str(patch).unwrap();
+        let result: Value = json::from_str(result).unwrap();
+        json_patch(&mut target, &patch).unwrap();
+        assert!(equal(&target, &result), "{}", json::to_string(&target));
+    }
+}
+
+#[test]
+fn errors() {
+    use miniserde::json;
+
+    let doc: Value = json::from_str(r#"{"foo": "bar", "baz": [1]}"#).unwrap();
+    for (patch, error) in [
+        (
+            r#"[{"op": "add", "path": "/baz/bat", "value": "qux"}]"#,
+            "operation 0: invalid array index at \"/baz/bat\"",
+        ),
+        (
+            r#"[{"op": "add", "path": "/a", "value": 1},
+                {"op": "test", "path": "/baz", "value": "bar"}]"#,
+            "operation 1: test failed",
+        ),
+        (
+            r#"[{"op": "move", "from": "/baz", "path": "/baz/0"}]"#,
+            "operation 0: cannot move a value into one of its children",
+        ),
+        (
+            r#"[{"op": "remove", "path": "/baz/1"}]"#,
+            "operation 0: no value at \"/baz/1\"",
+        ),
+        (
+            r#"[{"op": "replace", "path": "baz", "value": 1}]"#,
+            "operation 0: invalid JSON Pointer",
+        ),
+        (
+            r#"[{"op": "add", "path": "/a"}]"#,
+            "invalid JSON Patch: incomplete object, a required field may be missing \
+             (present keys: \"op\", \"path\") at /0",
+        ),
+        (
+            r#"[{"op": "frobnicate", "path": "/a"}]"#,
+            "invalid JSON Patch: invalid type: string \"frobnicate\", \
+             rejected by visitor.string at /0/op",
+        ),
+    ] {
+        let mut target = doc.clone();
+        let patch: Value = json::from_str(patch).unwrap();
+        let err = json_patch(&mut target, &patch).unwrap_err();
+        assert_eq!(err.to_string(), error);
+        assert!(equal(&target, &doc), "{}", json::to_string(&target));
+    }
+}
+
+#[test]
+fn diff() {
+    use miniserde::json;
+
+    let source: Value =
+        json::from_str(r#"{"a": [1, 2, 3], "b": {"c": 1, "d~/": 2}, "e": 1, "f": [1]}"#).unwrap();
+    let target: Value =
+        json::from_str(r#"{"a": [1, 5], "b": {"c": 1}, "e": "x", "f": [1, {}], "g": null}"#)
+            .unwrap();
+    let ops = json_patch_diff(&source, &target);
+    let expected: Value = json::from_str(
+        r#"[
+            {"op": "add", "path": "/g", "value": null},
+            {"op": "remove", "path": "/a/2"},
+            {"op": "replace", "path": "/a/1", "value": 5},
+            {"op": "remove", "path": "/b/d~0~1"},
+            {"op": "replace", "path": "/e", "value": "x"},
+            {"op": "add", "path": "/f/-", "value": {}}
+        ]"#,
+    )
+    .unwrap();
+    let patch = to_value(&ops);
+    assert!(equal(&patch, &expected), "{}", json::to_string(&patch));
+
+    let mut patched = source.clone();
+    json_patch(&mut patched, &patch).unwrap();
+    assert!(equal(&patched, &target));
+    assert!(json_patch_diff(&source, &source).is_empty());
+}
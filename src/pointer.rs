@@ -0,0 +1,406 @@
+use crate::de::from_value_detailed;
+use crate::error::DetailedError;
+use miniserde::{
+    json::{Object, Value},
+    Deserialize,
+};
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+use std::mem;
+
+/// Error resolving a JSON Pointer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PointerError {
+    /// The pointer is not valid RFC 6901 syntax: it is neither empty nor
+    /// starts with `/`, or it contains a `~` not followed by `0` or `1`.
+    Syntax,
+    /// Nothing exists at this pointer, which is the shortest prefix of the
+    /// requested one that could not be resolved.
+    NotFound(String),
+    /// A reference token applied to an array is neither `-` nor a decimal
+    /// index without leading zeros. Holds the pointer up to that token.
+    InvalidIndex(String),
+}
+
+impl Display for PointerError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PointerError::Syntax => formatter.write_str("invalid JSON Pointer"),
+            PointerError::NotFound(pointer) => write!(formatter, "no value at {:?}", pointer),
+            PointerError::InvalidIndex(pointer) => {
+                write!(formatter, "invalid array index at {:?}", pointer)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PointerError {}
+
+/// Error returned by `from_value_at`.
+#[derive(Clone, Debug)]
+pub enum FromValueAtError {
+    /// The pointer could not be resolved.
+    Pointer(PointerError),
+    /// The value at the pointer could not be deserialized. The error's path is
+    /// relative to the root of the document, not to the pointer.
+    Deserialize(DetailedError),
+}
+
+impl Display for FromValueAtError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FromValueAtError::Pointer(err) => Display::fmt(err, formatter),
+            FromValueAtError::Deserialize(err) => Display::fmt(err, formatter),
+        }
+    }
+}
+
+impl std::error::Error for FromValueAtError {}
+
+/// Deserialize the part of `v` designated by an RFC 6901 JSON Pointer, such
+/// as `/spec/template`. The empty pointer designates `v` itself.
+pub fn from_value_at<T: Deserialize>(v: &Value, pointer: &str) -> Result<T, FromValueAtError> {
+    let target = resolve(v, pointer).map_err(FromValueAtError::Pointer)?;
+    from_value_detailed(target)
+        .map_err(|err| FromValueAtError::Deserialize(err.with_prefix(pointer)))
+}
+
+/// Access and edit a `Value` by RFC 6901 JSON Pointer.
+///
+/// The empty pointer designates the whole Value. In arrays, `-` designates
+/// the position after the last element: it can be inserted at but never
+/// resolves to a value.
+pub trait PointerExt {
+    /// Get the value at `pointer`.
+    fn pointer(&self, pointer: &str) -> Result<&Value, PointerError>;
+
+    /// Get a mutable reference to the value at `pointer`.
+    fn pointer_mut(&mut self, pointer: &str) -> Result<&mut Value, PointerError>;
+
+    /// Insert `value` at `pointer`, creating missing intermediate objects,
+    /// and return the object member it replaced, if any.
+    ///
+    /// In arrays the value is inserted before the element at the index, or
+    /// appended for `-`; a `-` in the middle of the pointer appends a new
+    /// object. Intermediate values that are neither objects nor arrays are
+    /// never overwritten.
+    fn insert_at(&mut self, pointer: &str, value: Value) -> Result<Option<Value>, PointerError>;
+
+    /// Remove the value at `pointer` from its parent and return it. Array
+    /// elements after it are shifted down. Removing the root leaves `null`.
+    fn remove_at(&mut self, pointer: &str) -> Result<Value, PointerError>;
+
+    /// Replace the value at `pointer` with `null` and return it.
+    fn take_at(&mut self, pointer: &str) -> Result<Value, PointerError>;
+}
+
+impl PointerExt for Value {
+    fn pointer(&self, pointer: &str) -> Result<&Value, PointerError> {
+        resolve(self, pointer)
+    }
+
+    fn pointer_mut(&mut self, pointer: &str) -> Result<&mut Value, PointerError> {
+        resolve_mut(self, pointer)
+    }
+
+    fn insert_at(&mut self, pointer: &str, value: Value) -> Result<Option<Value>, PointerError> {
+        insert(self, pointer, value, true)
+    }
+
+    fn remove_at(&mut self, pointer: &str) -> Result<Value, PointerError> {
+        remove(self, pointer)
+    }
+
+    fn take_at(&mut self, pointer: &str) -> Result<Value, PointerError> {
+        Ok(mem::replace(resolve_mut(self, pointer)?, Value::Null))
+    }
+}
+
+pub(crate) fn resolve<'a>(v: &'a Value, pointer: &str) -> Result<&'a Value, PointerError> {
+    let mut v = v;
+    let mut end = 0;
+    for token in tokens(pointer)? {
+        end += 1 + token.len();
+        let token = unescape(token);
+        let next = match v {
+            Value::Object(o) => o.get(&*token),
+            Value::Array(a) => a.get(array_index(&token, &pointer[..end])?),
+            _ => None,
+        };
+        v = next.ok_or_else(|| PointerError::NotFound(pointer[..end].to_owned()))?;
+    }
+    Ok(v)
+}
+
+pub(crate) fn resolve_mut<'a>(
+    v: &'a mut Value,
+    pointer: &str,
+) -> Result<&'a mut Value, PointerError> {
+    walk_mut(v, pointer, false)
+}
+
+// Resolve `pointer` mutably. With `create`, missing object members become
+// empty objects and `-` appends an empty object to an array.
+fn walk_mut<'a>(
+    v: &'a mut Value,
+    pointer: &str,
+    create: bool,
+) -> Result<&'a mut Value, PointerError> {
+    let mut v = v;
+    let mut end = 0;
+    for token in tokens(pointer)? {
+        end += 1 + token.len();
+        let token = unescape(token);
+        let next = match v {
+            Value::Object(o) => {
+                if create {
+                    let empty = || Value::Object(Object::new());
+                    Some(o.entry(token.into_owned()).or_insert_with(empty))
+                } else {
+                    o.get_mut(&*token)
+                }
+            }
+            Value::Array(a) => {
+                if create && token == "-" {
+                    a.push(Value::Object(Object::new()));
+                    a.last_mut()
+                } else {
+                    a.get_mut(array_index(&token, &pointer[..end])?)
+                }
+            }
+            _ => None,
+        };
+        v = next.ok_or_else(|| PointerError::NotFound(pointer[..end].to_owned()))?;
+    }
+    Ok(v)
+}
+
+// Insert with the semantics of JSON Patch `add`, also creating missing
+// parents if `create` is set.
+pub(crate) fn insert(
+    v: &mut Value,
+    pointer: &str,
+    value: Value,
+    create: bool,
+) -> Result<Option<Value>, PointerError> {
+    let (parent, token) = match split(pointer)? {
+        Some(split) => split,
+        None => return Ok(Some(mem::replace(v, value))),
+    };
+    match walk_mut(v, parent, create)? {
+        Value::Object(o) => Ok(o.insert(token.into_owned(), value)),
+        Value::Array(a) => {
+            let i = if token == "-" {
+                a.len()
+            } else {
+                array_index(&token, pointer)?
+            };
+            if i > a.len() {
+                return Err(PointerError::NotFound(pointer.to_owned()));
+            }
+            a.insert(i, value);
+            Ok(None)
+        }
+        _ => Err(PointerError::NotFound(pointer.to_owned())),
+    }
+}
+
+pub(crate) fn remove(v: &mut Value, pointer: &str) -> Result<Value, PointerError> {
+    let (parent, token) = match split(pointer)? {
+        Some(split) => split,
+        None => return Ok(mem::replace(v, Value::Null)),
+    };
+    let removed = match resolve_mut(v, parent)? {
+        Value::Object(o) => o.remove(&*token),
+        Value::Array(a) => {
+            let i = array_index(&token, pointer)?;
+            if i < a.len() {
+                Some(a.remove(i))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+    removed.ok_or_else(|| PointerError::NotFound(pointer.to_owned()))
+}
+
+// Split a pointer into the pointer to its parent and its last reference
+// token, unescaped. The root has no parent.
+fn split(pointer: &str) -> Result<Option<(&str, Cow<'_, str>)>, PointerError> {
+    let _ = tokens(pointer)?;
+    Ok(pointer
+        .rfind('/')
+        .map(|i| (&pointer[..i], unescape(&pointer[i + 1..]))))
+}
+
+// Index designated by a token applied to an array. `-` is valid but never
+// designates an existing element.
+fn array_index(token: &str, pointer: &str) -> Result<usize, PointerError> {
+    if token == "-" {
+        return Err(PointerError::NotFound(pointer.to_owned()));
+    }
+    index(token).ok_or_else(|| PointerError::InvalidIndex(pointer.to_owned()))
+}
+
+// Split a pointer into its still escaped reference tokens.
+pub(crate) fn tokens(pointer: &str) -> Result<impl Iterator<Item = &str>, PointerError> {
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        return Err(PointerError::Syntax);
+    }
+    let mut chars = pointer.chars();
+    while let Some(c) = chars.next() {
+        if c == '~' && !matches!(chars.next(), Some('0') | Some('1')) {
+            return Err(PointerError::Syntax);
+        }
+    }
+    Ok(pointer.split('/').skip(1))
+}
+
+pub(crate) fn unescape(token: &str) -> Cow<'_, str> {
+    if token.contains('~') {
+        Cow::Owned(token.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+pub(crate) fn escape(token: &str) -> Cow<'_, str> {
+    if token.contains(['~', '/']) {
+        Cow::Owned(token.replace('~', "~0").replace('/', "~1"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+// Array index as allowed by RFC 6901: no sign and no leading zeros.
+pub(crate) fn index(token: &str) -> Option<usize> {
+    let valid = match token.as_bytes() {
+        [b'0'] => true,
+        [b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+        _ => false,
+    };
+    if valid {
+        token.parse().ok()
+    } else {
+        None
+    }
+}
+
+#[test]
+#[allow(non_local_definitions)]
+fn at() {
+    use crate::error::{Callback, ErrorKind, Unexpected};
+    use miniserde::json;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Container {
+        image: String,
+    }
+
+    let v: Value = json::from_str(
+        r#"{"spec": {"containers": [{"image": "a"}, {"image": 1}]}, "a/b": {"~x": true}, "": 0}"#,
+    )
+    .unwrap();
+    let c: Container = from_value_at(&v, "/spec/containers/0").unwrap();
+    assert_eq!(c.image, "a");
+    assert!(from_value_at::<bool>(&v, "/a~1b/~0x").unwrap());
+    assert_eq!(from_value_at::<u8>(&v, "/").unwrap(), 0);
+    assert!(from_value_at::<Value>(&v, "").is_ok());
+
+    match from_value_at::<Container>(&v, "/spec/containers/1") {
+        Err(FromValueAtError::Deserialize(err)) => {
+            assert_eq!(err.path(), "/spec/containers/1/image");
+            assert_eq!(err.callback(), Callback::Nonnegative);
+            assert_eq!(err.kind(), &ErrorKind::InvalidType(Unexpected::Unsigned(1)));
+        }
+        other => panic!("{:?}", other),
+    }
+
+    for (pointer, error) in [
+        (
+            "/spec/containers/2/image",
+            PointerError::NotFound("/spec/containers/2".into()),
+        ),
+        (
+            "/spec/containers/01",
+            PointerError::InvalidIndex("/spec/containers/01".into()),
+        ),
+        (
+            "/spec/containers/x/image",
+            PointerError::InvalidIndex("/spec/containers/x".into()),
+        ),
+        (
+            "/spec/containers/-",
+            PointerError::NotFound("/spec/containers/-".into()),
+        ),
+        ("/spec/x", PointerError::NotFound("/spec/x".into())),
+        ("/a~1b/~0x/y", PointerError::NotFound("/a~1b/~0x/y".into())),
+        ("spec", PointerError::Syntax),
+        ("/a~2b", PointerError::Syntax),
+        ("/a~", PointerError::Syntax),
+    ] {
+        match from_value_at::<Value>(&v, pointer) {
+            Err(FromValueAtError::Pointer(err)) => assert_eq!(err, error),
+            other => panic!("{}: {:?}", pointer, other),
+        }
+    }
+}
+
+#[test]
+fn edit() {
+    use crate::eq::equal;
+    use miniserde::json::{self, Number};
+
+    let mut v: Value = json::from_str(r#"{"a": [1, 2], "b": {"c": true}}"#).unwrap();
+    let one = || Value::Number(Number::U64(1));
+    assert!(v.insert_at("/a/-", one()).unwrap().is_none());
+    assert!(v.insert_at("/a/0", Value::Null).unwrap().is_none());
+    assert!(v.insert_at("/b/c", one()).unwrap().is_some());
+    assert!(v.insert_at("/a/-/w", one()).unwrap().is_none());
+    assert!(v.insert_at("/x/y~1z/w", one()).unwrap().is_none());
+    *v.pointer_mut("/b").unwrap() = Value::Bool(false);
+    let expected: Value =
+        json::from_str(r#"{"a": [null, 1, 2, 1, {"w": 1}], "b": false, "x": {"y/z": {"w": 1}}}"#)
+            .unwrap();
+    assert!(equal(&v, &expected), "{}", json::to_string(&v));
+
+    assert!(equal(&v.remove_at("/a/0").unwrap(), &Value::Null));
+    assert!(equal(&v.take_at("/a/3/w").unwrap(), &one()));
+    assert!(equal(v.pointer("/a/3/w").unwrap(), &Value::Null));
+    assert!(equal(
+        &v.take_at("/x/y~1z").unwrap(),
+        expected.pointer("/x/y~1z").unwrap()
+    ));
+
+    for (result, error) in [
+        (
+            v.insert_at("/a/5", one()),
+            PointerError::NotFound("/a/5".into()),
+        ),
+        (
+            v.insert_at("/a/01", one()),
+            PointerError::InvalidIndex("/a/01".into()),
+        ),
+        (
+            v.insert_at("/b/c", one()),
+            PointerError::NotFound("/b/c".into()),
+        ),
+        (v.insert_at("a", one()), PointerError::Syntax),
+    ] {
+        assert_eq!(result.unwrap_err(), error);
+    }
+    for (result, error) in [
+        (v.remove_at("/a/-"), PointerError::NotFound("/a/-".into())),
+        (v.remove_at("/a/4"), PointerError::NotFound("/a/4".into())),
+        (v.remove_at("/q"), PointerError::NotFound("/q".into())),
+        (
+            v.take_at("/a/-1"),
+            PointerError::InvalidIndex("/a/-1".into()),
+        ),
+        (v.take_at("/b/c"), PointerError::NotFound("/b/c".into())),
+    ] {
+        assert_eq!(result.unwrap_err(), error);
+    }
+    assert!(v.pointer("/a/-").is_err());
+}
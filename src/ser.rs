@@ -0,0 +1,160 @@
+use miniserde::{
+    json::{Array, Number, Object, Value},
+    ser::{Fragment, Map, Seq},
+    Serialize,
+};
+use std::borrow::Cow;
+use std::mem;
+
+/// Serialize any `miniserde::Serialize` type into a JSON `Value`.
+///
+/// Like `from_value`, this does not recurse: partially built arrays and
+/// objects are kept on an explicit stack, so arbitrarily deep structures
+/// cannot overflow the call stack.
+pub fn to_value<T: ?Sized + Serialize>(value: &T) -> Value {
+    to_value_impl(&value)
+}
+
+struct Serializer<'a> {
+    stack: Vec<Layer<'a>>,
+}
+
+enum Layer<'a> {
+    Seq(Box<dyn Seq + 'a>, Array),
+    Map(Box<dyn Map + 'a>, Object, String),
+}
+
+impl<'a> Drop for Serializer<'a> {
+    fn drop(&mut self) {
+        // Drop layers in reverse order.
+        while !self.stack.is_empty() {
+            self.stack.pop();
+        }
+    }
+}
+
+fn to_value_impl(value: &dyn Serialize) -> Value {
+    let mut serializer = Serializer { stack: Vec::new() };
+    let mut fragment = value.begin();
+
+    loop {
+        let mut value = match fragment {
+            Fragment::Null => Value::Null,
+            Fragment::Bool(b) => Value::Bool(b),
+            Fragment::Str(s) => Value::String(s.into_owned()),
+            Fragment::U64(n) => Value::Number(Number::U64(n)),
+            Fragment::I64(n) => Value::Number(Number::I64(n)),
+            Fragment::F64(n) => Value::Number(Number::F64(n)),
+            Fragment::Seq(mut seq) => {
+                // invariant: `seq` must outlive `first`
+                match careful!(seq.next() as Option<&dyn Serialize>) {
+                    Some(first) => {
+                        serializer.stack.push(Layer::Seq(seq, Array::new()));
+                        fragment = first.begin();
+                        continue;
+                    }
+                    None => Value::Array(Array::new()),
+                }
+            }
+            Fragment::Map(mut map) => {
+                // invariant: `map` must outlive `first`
+                match careful!(map.next() as Option<(Cow<str>, &dyn Serialize)>) {
+                    Some((key, first)) => {
+                        let key = key.into_owned();
+                        serializer.stack.push(Layer::Map(map, Object::new(), key));
+                        fragment = first.begin();
+                        continue;
+                    }
+                    None => Value::Object(Object::new()),
+                }
+            }
+        };
+
+        loop {
+            match serializer.stack.last_mut() {
+                Some(Layer::Seq(seq, array)) => {
+                    array.push(value);
+                    // invariant: `seq` must outlive `next`
+                    if let Some(next) = careful!(seq.next() as Option<&dyn Serialize>) {
+                        fragment = next.begin();
+                        break;
+                    }
+                }
+                Some(Layer::Map(map, object, key)) => {
+                    object.insert(mem::take(key), value);
+                    // invariant: `map` must outlive `next`
+                    if let Some((k, next)) =
+                        careful!(map.next() as Option<(Cow<str>, &dyn Serialize)>)
+                    {
+                        *key = k.into_owned();
+                        fragment = next.begin();
+                        break;
+                    }
+                }
+                None => return value,
+            }
+            value = match serializer.stack.pop() {
+                Some(Layer::Seq(_, array)) => Value::Array(array),
+                Some(Layer::Map(_, object, _)) => Value::Object(object),
+                None => unreachable!(),
+            };
+        }
+    }
+}
+
+#[test]
+#[allow(non_local_definitions)]
+fn round_trip() {
+    use miniserde::{json, Deserialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Inner {
+        name: String,
+        tags: Vec<String>,
+    }
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct S {
+        id: u32,
+        offset: i64,
+        ratio: f64,
+        enabled: bool,
+        missing: Option<Inner>,
+        inner: Vec<Inner>,
+    }
+    let s = S {
+        id: 3,
+        offset: -7,
+        ratio: 0.5,
+        enabled: true,
+        missing: None,
+        inner: vec![
+            Inner {
+                name: "a".into(),
+                tags: vec![],
+            },
+            Inner {
+                name: "b".into(),
+                tags: vec!["x".into(), "y".into()],
+            },
+        ],
+    };
+    let v = to_value(&s);
+    let parsed: Value = json::from_str(&json::to_string(&s)).unwrap();
+    assert_eq!(json::to_string(&parsed), json::to_string(&v));
+    assert_eq!(s, crate::from_value::<S>(&v).unwrap());
+}
+
+#[test]
+fn deep() {
+    let mut v = Value::Null;
+    for _ in 0..100_000 {
+        let mut array = Array::new();
+        array.push(v);
+        v = Value::Array(array);
+    }
+    let copy = to_value(&v);
+    assert_eq!(
+        miniserde::json::to_string(&v),
+        miniserde::json::to_string(&copy)
+    );
+}
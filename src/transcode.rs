@@ -0,0 +1,146 @@
+use miniserde::{
+    de::{self, Visitor},
+    ser::{self, Fragment},
+    Deserialize, Error, Result, Serialize,
+};
+use std::borrow::Cow;
+
+/// Convert between two types with the same JSON shape, without building an
+/// intermediate `Value`.
+///
+/// The fragments produced by `value` are fed straight to the visitors of `D`.
+/// Like `from_value`, this uses an explicit stack instead of recursion.
+pub fn transcode<S, D>(value: &S) -> Result<D>
+where
+    S: ?Sized + Serialize,
+    D: Deserialize,
+{
+    let mut out = None;
+    transcode_impl(&value, D::begin(&mut out))?;
+    out.ok_or(Error)
+}
+
+struct Transcoder<'a> {
+    stack: Vec<Layer<'a>>,
+}
+
+enum Layer<'a> {
+    Seq(Box<dyn ser::Seq + 'a>, Box<dyn de::Seq>),
+    Map(Box<dyn ser::Map + 'a>, Box<dyn de::Map>),
+}
+
+impl<'a> Drop for Transcoder<'a> {
+    fn drop(&mut self) {
+        // Drop layers in reverse order.
+        while !self.stack.is_empty() {
+            self.stack.pop();
+        }
+    }
+}
+
+fn transcode_impl(value: &dyn Serialize, visitor: &mut dyn Visitor) -> Result<()> {
+    let mut transcoder = Transcoder { stack: Vec::new() };
+    let mut fragment = value.begin();
+    let mut visitor = visitor;
+
+    loop {
+        match fragment {
+            Fragment::Null => visitor.null()?,
+            Fragment::Bool(b) => visitor.boolean(b)?,
+            Fragment::Str(s) => visitor.string(&s)?,
+            Fragment::U64(n) => visitor.nonnegative(n)?,
+            Fragment::I64(n) => visitor.negative(n)?,
+            Fragment::F64(n) => visitor.float(n)?,
+            Fragment::Seq(seq) => {
+                let de = careful!(visitor.seq()? as Box<dyn de::Seq>);
+                transcoder.stack.push(Layer::Seq(seq, de));
+            }
+            Fragment::Map(map) => {
+                let de = careful!(visitor.map()? as Box<dyn de::Map>);
+                transcoder.stack.push(Layer::Map(map, de));
+            }
+        }
+
+        loop {
+            match transcoder.stack.last_mut() {
+                Some(Layer::Seq(ser, de)) => {
+                    // invariant: `ser` must outlive `next`
+                    match careful!(ser.next() as Option<&dyn Serialize>) {
+                        Some(next) => {
+                            visitor = careful!(de.element()? as &mut dyn Visitor);
+                            fragment = next.begin();
+                            break;
+                        }
+                        None => de.finish()?,
+                    }
+                }
+                Some(Layer::Map(ser, de)) => {
+                    // invariant: `ser` must outlive `next`
+                    match careful!(ser.next() as Option<(Cow<str>, &dyn Serialize)>) {
+                        Some((key, next)) => {
+                            visitor = careful!(de.key(&key)? as &mut dyn Visitor);
+                            fragment = next.begin();
+                            break;
+                        }
+                        None => de.finish()?,
+                    }
+                }
+                None => return Ok(()),
+            }
+            transcoder.stack.pop();
+        }
+    }
+}
+
+#[test]
+#[allow(non_local_definitions)]
+fn dto() {
+    #[derive(Serialize)]
+    struct Dto {
+        name: String,
+        ports: Vec<u16>,
+        extra: Option<bool>,
+        ratio: f64,
+    }
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Domain {
+        name: String,
+        ports: Vec<u32>,
+        ratio: f64,
+    }
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Narrow {
+        ports: Vec<u8>,
+    }
+
+    let dto = Dto {
+        name: "x".into(),
+        ports: vec![80, 443],
+        extra: None,
+        ratio: -1.5,
+    };
+    assert_eq!(
+        transcode::<_, Domain>(&dto).unwrap(),
+        Domain {
+            name: "x".into(),
+            ports: vec![80, 443],
+            ratio: -1.5,
+        }
+    );
+    assert!(transcode::<_, Narrow>(&dto).is_err());
+}
+
+#[test]
+fn deep() {
+    use miniserde::json::{self, Array, Value};
+
+    let mut v = Value::Null;
+    for _ in 0..100_000 {
+        let mut array = Array::new();
+        array.push(v);
+        v = Value::Array(array);
+    }
+    let copy: Value = transcode(&v).unwrap();
+    assert_eq!(json::to_string(&v), json::to_string(&copy));
+}
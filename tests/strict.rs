@@ -0,0 +1,46 @@
+// Unknown keys as seen from a downstream crate, whose derived impls hand out
+// miniserde's ignore sink from their own code.
+
+#![allow(non_local_definitions)]
+
+use miniserde::{json, json::Value, Deserialize};
+use miniserde_from_value::{ErrorKind, FromValueOptions};
+
+#[derive(Deserialize, Debug)]
+struct Server {
+    host: String,
+    port: u16,
+}
+
+fn input() -> Value {
+    json::from_str(r#"{"host": "a", "port": 1, "prot": 2}"#).unwrap()
+}
+
+#[test]
+fn deny_unknown_keys() {
+    let err = FromValueOptions::new()
+        .deny_unknown_keys(true)
+        .from_value::<Server>(&input())
+        .unwrap_err();
+    assert_eq!(err.path(), "/prot");
+    assert_eq!(err.kind(), &ErrorKind::UnknownKey);
+
+    let v: Value = json::from_str(r#"{"host": "a", "port": 1}"#).unwrap();
+    let server = FromValueOptions::new()
+        .deny_unknown_keys(true)
+        .from_value::<Server>(&v)
+        .unwrap();
+    assert_eq!((server.host.as_str(), server.port), ("a", 1));
+}
+
+#[test]
+fn report_ignored_keys() {
+    let (server, ignored) = FromValueOptions::new()
+        .from_value_report::<Server>(&input())
+        .unwrap();
+    assert_eq!((server.host.as_str(), server.port), ("a", 1));
+    assert_eq!(ignored, ["/prot"]);
+
+    let (_, ignored) = miniserde_from_value::from_value_report::<Server>(&input()).unwrap();
+    assert_eq!(ignored, ["/prot"]);
+}